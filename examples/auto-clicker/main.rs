@@ -31,7 +31,9 @@ fn main() {
 
     let mut enigo = Enigo::new();
 
-    loop {
-        button.click().sleep_ms(interval).run_with(&mut enigo);
-    }
+    button
+        .click()
+        .sleep_ms(interval)
+        .forever()
+        .run_with(&mut enigo);
 }
@@ -1,6 +1,6 @@
-use std::{io::stdin, time::Instant};
+use std::{io::stdin, time::Duration};
 
-use kemuler::{prelude::*, simulators::enigo::Enigo};
+use kemuler::{common_inputs::mouse_path::Path, prelude::*, simulators::enigo::Enigo};
 
 fn prompt<T>(message: &str) -> T
 where
@@ -19,26 +19,16 @@ fn read_line() -> String {
 
 fn main() {
     let radius = prompt::<f64>("Radius in pixels:");
-    let speed = prompt::<f64>("Speed:");
-    let offset_x = radius;
-    let offset_y = radius;
+    let seconds = prompt::<f64>("Seconds per round:");
+    let center = (radius as i32, radius as i32);
 
     let mut enigo = Enigo::new();
 
-    let mut delta_time = 0f64;
-    let mut time_elasped = 0f64;
+    let circle = MousePosition
+        .move_along(Path::circle(center, radius))
+        .duration(Duration::from_secs_f64(seconds))
+        .rate(120);
     loop {
-        let previous_instant = Instant::now();
-        time_elasped += delta_time;
-
-        let x = (time_elasped * speed).cos() * radius;
-        let y = (time_elasped * speed).sin() * radius;
-        let pos_x = x + offset_x;
-        let pos_y = y + offset_y;
-        MousePosition
-            .move_to(pos_x as i32, pos_y as i32)
-            .run_with(&mut enigo);
-
-        delta_time = (Instant::now() - previous_instant).as_secs_f64();
+        circle.clone().run_with(&mut enigo);
     }
 }
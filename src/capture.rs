@@ -0,0 +1,7 @@
+//! Module for capturing real input into a [`Timeline`](crate::timeline::Timeline)
+//!
+//! Captured timelines can be replayed with any simulator,
+//! saved, or optimized with [`Optimize`](crate::timeline::optimize::Optimize)
+//! since captures usually contain many small mouse moves.
+
+pub mod evdev;
@@ -0,0 +1,364 @@
+//! Capture input from Linux evdev devices.
+//!
+//! Devices at `/dev/input/event*` produce a stream of `input_event` records
+//! which [`EvdevRecorder`] reads from any [`io::Read`] and converts into
+//! a [`Timeline`] of common input events.
+//! Reading a device usually needs root or being in the `input` group.
+//!
+//! - Keys are converted into [`Key`] if there is one,
+//!   otherwise into the [`Char`] of the key on a US QWERTY layout.
+//! - Relative pointer motion is converted into relative mouse moves,
+//!   one per report.
+//! - Wheels are converted into scrolls, positive y scrolls down.
+//! - Key repeats and everything else are ignored.
+//!
+//! ```
+//! use std::time::Duration;
+//! use kemuler::prelude::*;
+//! use kemuler::capture::evdev::{EvdevRecorder, Record, RecordFormat, EV_KEY, EV_SYN};
+//! use kemuler::timeline::Event;
+//!
+//! // a press and a release of `A`, 50 ms apart
+//! let mut bytes = Vec::new();
+//! for (time, kind, code, value) in [
+//!     (0, EV_KEY, 30, 1),
+//!     (0, EV_SYN, 0, 0),
+//!     (50, EV_KEY, 30, 0),
+//!     (50, EV_SYN, 0, 0),
+//! ] {
+//!     let time = Duration::from_millis(time);
+//!     Record { time, kind, code, value }.write_to(&mut bytes, RecordFormat::Time64);
+//! }
+//!
+//! let timeline = EvdevRecorder::with_format(&bytes[..], RecordFormat::Time64)
+//!     .record()
+//!     .unwrap();
+//! assert_eq!(timeline.len(), 2);
+//! assert_eq!(timeline.events[1].offset, Duration::from_millis(50));
+//! assert_eq!(timeline.events[1].event, Event::from(Char('a').up()));
+//! ```
+
+use std::{
+    fs::File,
+    io::{self, BufReader},
+    path::Path,
+    time::Duration,
+};
+
+use crate::{
+    common_inputs::{ButtonLike, Char, Key, MouseButton, MousePosition, MouseScroll},
+    simulators::virtual_state::Button,
+    timeline::{Event, Timed, Timeline},
+};
+
+#[cfg(test)]
+mod test;
+
+pub const EV_SYN: u16 = 0x00;
+pub const EV_KEY: u16 = 0x01;
+pub const EV_REL: u16 = 0x02;
+
+pub const SYN_REPORT: u16 = 0x00;
+
+pub const REL_X: u16 = 0x00;
+pub const REL_Y: u16 = 0x01;
+pub const REL_HWHEEL: u16 = 0x06;
+pub const REL_WHEEL: u16 = 0x08;
+
+pub const BTN_LEFT: u16 = 0x110;
+pub const BTN_RIGHT: u16 = 0x111;
+pub const BTN_MIDDLE: u16 = 0x112;
+
+/// Size of the timestamp in a record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RecordFormat {
+    /// 64 bit seconds and microseconds, 24 bytes per record
+    Time64,
+    /// 32 bit seconds and microseconds, 16 bytes per record
+    Time32,
+}
+
+impl RecordFormat {
+    /// Format of the system this is compiled for.
+    pub fn native() -> RecordFormat {
+        if cfg!(target_pointer_width = "64") {
+            RecordFormat::Time64
+        } else {
+            RecordFormat::Time32
+        }
+    }
+
+    /// Size of a record in bytes.
+    pub fn size(self) -> usize {
+        match self {
+            RecordFormat::Time64 => 24,
+            RecordFormat::Time32 => 16,
+        }
+    }
+}
+
+impl Default for RecordFormat {
+    fn default() -> Self {
+        RecordFormat::native()
+    }
+}
+
+/// A raw `input_event` record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Record {
+    /// Time the event happened, since an arbitrary point
+    pub time: Duration,
+    /// `type` of the event, `EV_*`
+    pub kind: u16,
+    pub code: u16,
+    pub value: i32,
+}
+
+impl Record {
+    /// Parse a record from bytes in native endianness.
+    ///
+    /// # Panics
+    /// If `bytes` is shorter than `format.size()`.
+    pub fn parse(bytes: &[u8], format: RecordFormat) -> Record {
+        let (seconds, micros, rest) = match format {
+            RecordFormat::Time64 => (
+                u64::from_ne_bytes(bytes[0..8].try_into().unwrap()),
+                u64::from_ne_bytes(bytes[8..16].try_into().unwrap()),
+                &bytes[16..24],
+            ),
+            RecordFormat::Time32 => (
+                u32::from_ne_bytes(bytes[0..4].try_into().unwrap()) as u64,
+                u32::from_ne_bytes(bytes[4..8].try_into().unwrap()) as u64,
+                &bytes[8..16],
+            ),
+        };
+        Record {
+            time: Duration::from_secs(seconds) + Duration::from_micros(micros),
+            kind: u16::from_ne_bytes(rest[0..2].try_into().unwrap()),
+            code: u16::from_ne_bytes(rest[2..4].try_into().unwrap()),
+            value: i32::from_ne_bytes(rest[4..8].try_into().unwrap()),
+        }
+    }
+
+    /// Append this record as bytes in native endianness.
+    pub fn write_to(&self, bytes: &mut Vec<u8>, format: RecordFormat) {
+        let seconds = self.time.as_secs();
+        let micros = self.time.subsec_micros();
+        match format {
+            RecordFormat::Time64 => {
+                bytes.extend(seconds.to_ne_bytes());
+                bytes.extend((micros as u64).to_ne_bytes());
+            }
+            RecordFormat::Time32 => {
+                bytes.extend((seconds as u32).to_ne_bytes());
+                bytes.extend(micros.to_ne_bytes());
+            }
+        }
+        bytes.extend(self.kind.to_ne_bytes());
+        bytes.extend(self.code.to_ne_bytes());
+        bytes.extend(self.value.to_ne_bytes());
+    }
+}
+
+/// Reads evdev records and converts them into a [`Timeline`].
+///
+/// The first record read is at offset zero.
+#[derive(Debug)]
+pub struct EvdevRecorder<R> {
+    reader: R,
+    format: RecordFormat,
+    /// time of the first record
+    start: Option<Duration>,
+    timeline: Timeline,
+    /// relative motion not yet reported
+    pending_move: (i32, i32),
+    pending_scroll: (i32, i32),
+}
+
+impl EvdevRecorder<BufReader<File>> {
+    /// Open a device, for example `/dev/input/event3`.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<EvdevRecorder<BufReader<File>>> {
+        Ok(EvdevRecorder::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<R: io::Read> EvdevRecorder<R> {
+    /// Read records in the native format.
+    pub fn new(reader: R) -> EvdevRecorder<R> {
+        EvdevRecorder::with_format(reader, RecordFormat::native())
+    }
+
+    pub fn with_format(reader: R, format: RecordFormat) -> EvdevRecorder<R> {
+        EvdevRecorder {
+            reader,
+            format,
+            start: None,
+            timeline: Timeline::new(),
+            pending_move: (0, 0),
+            pending_scroll: (0, 0),
+        }
+    }
+
+    /// Events recorded so far.
+    pub fn timeline(&self) -> &Timeline {
+        &self.timeline
+    }
+
+    /// Read the next raw record, `None` at the end of the reader.
+    pub fn read_record(&mut self) -> io::Result<Option<Record>> {
+        let mut buffer = [0; 24];
+        let buffer = &mut buffer[..self.format.size()];
+        match self.reader.read_exact(buffer) {
+            Ok(()) => Ok(Some(Record::parse(buffer, self.format))),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Record until the end of the reader.
+    pub fn record(self) -> io::Result<Timeline> {
+        self.record_while(|_| true)
+    }
+
+    /// Record until the end of the reader
+    /// or until `keep_going` returns `false` for a recorded event.
+    /// That event is still in the timeline.
+    ///
+    /// A device never ends so this is how to stop recording,
+    /// for example when a key is pressed.
+    pub fn record_while(
+        mut self,
+        mut keep_going: impl FnMut(&Timed) -> bool,
+    ) -> io::Result<Timeline> {
+        while let Some(record) = self.read_record()? {
+            let recorded = self.timeline.len();
+            self.feed(record);
+            if self.timeline.events[recorded..]
+                .iter()
+                .any(|e| !keep_going(e))
+            {
+                break;
+            }
+        }
+        self.flush();
+        Ok(self.timeline)
+    }
+
+    /// Read until the next event and take it out of the timeline,
+    /// `None` at the end of the reader.
+    ///
+    /// This is for reacting to events as they happen,
+    /// events taken out are not in the recorded timeline.
+    pub fn next_event(&mut self) -> io::Result<Option<Event>> {
+        while self.timeline.is_empty() {
+            match self.read_record()? {
+                Some(record) => self.feed(record),
+                None => {
+                    self.flush();
+                    if self.timeline.is_empty() {
+                        return Ok(None);
+                    }
+                }
+            }
+        }
+        Ok(Some(self.timeline.events.remove(0).event))
+    }
+
+    /// Convert a record and add the result to the timeline.
+    pub fn feed(&mut self, record: Record) {
+        let start = *self.start.get_or_insert(record.time);
+        let offset = record.time.saturating_sub(start);
+        self.timeline.duration = self.timeline.duration.max(offset);
+        match (record.kind, record.code) {
+            (EV_SYN, SYN_REPORT) => self.flush(),
+            (EV_REL, REL_X) => self.pending_move.0 += record.value,
+            (EV_REL, REL_Y) => self.pending_move.1 += record.value,
+            (EV_REL, REL_HWHEEL) => self.pending_scroll.0 += record.value,
+            (EV_REL, REL_WHEEL) => self.pending_scroll.1 -= record.value,
+            // 2 is auto repeat
+            (EV_KEY, code) if record.value == 0 || record.value == 1 => {
+                if let Some(button) = button_from_code(code) {
+                    self.flush();
+                    let is_down = record.value == 1;
+                    self.timeline.push(match button {
+                        Button::Key(key) => Event::from(key.set_to(is_down)),
+                        Button::MouseButton(button) => Event::from(button.set_to(is_down)),
+                        Button::Char(c) => Event::from(c.set_to(is_down)),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Push pending relative motion.
+    fn flush(&mut self) {
+        let (x, y) = std::mem::take(&mut self.pending_move);
+        if (x, y) != (0, 0) {
+            self.timeline.push(MousePosition.move_by(x, y));
+        }
+        let (x, y) = std::mem::take(&mut self.pending_scroll);
+        if (x, y) != (0, 0) {
+            self.timeline.push(MouseScroll.scroll_by(x, y));
+        }
+    }
+}
+
+/// Convert an evdev key code (`KEY_*` or `BTN_*`) into an input.
+///
+/// Keys without a [`Key`] are converted into the [`Char`] of the key
+/// on a US QWERTY layout, unshifted.
+pub fn button_from_code(code: u16) -> Option<Button> {
+    const ROW_1: &str = "1234567890-=";
+    const ROW_Q: &str = "qwertyuiop[]";
+    const ROW_A: &str = "asdfghjkl;'`";
+    const ROW_Z: &str = "zxcvbnm,./";
+    let char_in = |row: &str, first: u16| {
+        row.chars()
+            .nth(code.checked_sub(first)? as usize)
+            .map(|c| Button::Char(Char(c)))
+    };
+    let key = match code {
+        1 => Key::Escape,
+        2..=13 => return char_in(ROW_1, 2),
+        14 => Key::Backspace,
+        15 => Key::Tab,
+        16..=27 => return char_in(ROW_Q, 16),
+        28 | 96 => Key::Enter,
+        29 | 97 => Key::Control,
+        30..=41 => return char_in(ROW_A, 30),
+        42 | 54 => Key::Shift,
+        43 => return Some(Button::Char(Char('\\'))),
+        44..=53 => return char_in(ROW_Z, 44),
+        56 | 100 => Key::Alt,
+        57 => Key::Space,
+        58 => Key::CapsLock,
+        59 => Key::F1,
+        60 => Key::F2,
+        61 => Key::F3,
+        62 => Key::F4,
+        63 => Key::F5,
+        64 => Key::F6,
+        65 => Key::F7,
+        66 => Key::F8,
+        67 => Key::F9,
+        68 => Key::F10,
+        87 => Key::F11,
+        88 => Key::F12,
+        102 => Key::Home,
+        103 => Key::UpArrow,
+        104 => Key::PageUp,
+        105 => Key::LeftArrow,
+        106 => Key::RightArrow,
+        107 => Key::End,
+        108 => Key::DownArrow,
+        109 => Key::PageDown,
+        111 => Key::Delete,
+        125 | 126 => Key::Meta,
+        BTN_LEFT => return Some(Button::MouseButton(MouseButton::Left)),
+        BTN_RIGHT => return Some(Button::MouseButton(MouseButton::Right)),
+        BTN_MIDDLE => return Some(Button::MouseButton(MouseButton::Middle)),
+        _ => return None,
+    };
+    Some(Button::Key(key))
+}
@@ -0,0 +1,119 @@
+use std::time::Duration;
+
+use crate::prelude::*;
+use crate::timeline::{Event, Timed};
+
+use super::*;
+
+fn bytes(records: &[(u64, u16, u16, i32)], format: RecordFormat) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for &(time, kind, code, value) in records {
+        let time = Duration::from_millis(time);
+        Record {
+            time,
+            kind,
+            code,
+            value,
+        }
+        .write_to(&mut bytes, format);
+    }
+    bytes
+}
+
+fn at(ms: u64, event: impl Into<Event>) -> Timed {
+    Timed {
+        offset: Duration::from_millis(ms),
+        event: event.into(),
+    }
+}
+
+#[test]
+fn evdev_record_round_trip() {
+    for format in [RecordFormat::Time64, RecordFormat::Time32] {
+        let record = Record {
+            time: Duration::new(1_700_000_000, 123_456_000),
+            kind: EV_REL,
+            code: REL_Y,
+            value: -7,
+        };
+        let mut bytes = Vec::new();
+        record.write_to(&mut bytes, format);
+        assert_eq!(bytes.len(), format.size());
+        assert_eq!(Record::parse(&bytes, format), record);
+    }
+}
+
+#[test]
+fn evdev_record_mouse() {
+    let bytes = bytes(
+        &[
+            (1000, EV_REL, REL_X, 3),
+            (1000, EV_REL, REL_Y, -2),
+            (1000, EV_SYN, SYN_REPORT, 0),
+            (1008, EV_KEY, BTN_LEFT, 1),
+            (1008, EV_REL, REL_X, 1),
+            (1008, EV_SYN, SYN_REPORT, 0),
+            (1016, EV_REL, REL_WHEEL, 1),
+            (1016, EV_SYN, SYN_REPORT, 0),
+            (1024, EV_KEY, BTN_LEFT, 0),
+            (1024, EV_SYN, SYN_REPORT, 0),
+            (1030, EV_SYN, SYN_REPORT, 0),
+        ],
+        RecordFormat::Time32,
+    );
+    let timeline = EvdevRecorder::with_format(&bytes[..], RecordFormat::Time32)
+        .record()
+        .unwrap();
+    assert_eq!(
+        timeline.events,
+        vec![
+            at(0, MousePosition.move_by(3, -2)),
+            at(8, MouseButton::Left.down()),
+            at(8, MousePosition.move_by(1, 0)),
+            at(16, MouseScroll.scroll_by(0, -1)),
+            at(24, MouseButton::Left.up()),
+        ]
+    );
+    assert_eq!(timeline.duration, Duration::from_millis(30));
+}
+
+#[test]
+fn evdev_record_keys() {
+    let bytes = bytes(
+        &[
+            (0, EV_KEY, 42, 1),
+            (0, EV_KEY, 16, 1),
+            (5, EV_KEY, 16, 2),
+            (10, EV_KEY, 16, 0),
+            (10, EV_KEY, 0x2ff, 1),
+            (12, EV_KEY, 42, 0),
+            (20, EV_KEY, 1, 1),
+            (30, EV_KEY, 1, 0),
+        ],
+        RecordFormat::Time64,
+    );
+    let timeline = EvdevRecorder::with_format(&bytes[..], RecordFormat::Time64)
+        .record_while(|timed| timed.event != Event::from(Key::Escape.down()))
+        .unwrap();
+    assert_eq!(
+        timeline.events,
+        vec![
+            at(0, Key::Shift.down()),
+            at(0, Char('q').down()),
+            at(10, Char('q').up()),
+            at(12, Key::Shift.up()),
+            at(20, Key::Escape.down()),
+        ]
+    );
+}
+
+#[test]
+fn evdev_button_from_code() {
+    assert_eq!(button_from_code(2), Some(Button::Char(Char('1'))));
+    assert_eq!(button_from_code(13), Some(Button::Char(Char('='))));
+    assert_eq!(button_from_code(38), Some(Button::Char(Char('l'))));
+    assert_eq!(button_from_code(41), Some(Button::Char(Char('`'))));
+    assert_eq!(button_from_code(53), Some(Button::Char(Char('/'))));
+    assert_eq!(button_from_code(97), Some(Button::Key(Key::Control)));
+    assert_eq!(button_from_code(0), None);
+}
@@ -0,0 +1,284 @@
+//! Keyboard shortcuts from strings like `"ctrl+shift+t"`.
+//!
+//! ```
+//! # use kemuler::simulators::string_event_logger::StringEventLogger as Simulator;
+//! # use kemuler::assert_event;
+//! use kemuler::prelude::*;
+//! use kemuler::chord::Chord;
+//!
+//! let mut s = Simulator::new();
+//!
+//! let chord: Chord = "ctrl+shift+t".parse().unwrap();
+//! chord.clone().run_with(&mut s);
+//!
+//! // same as
+//! // Char('t').click().during(Key::Shift.down()).during(Key::Control.down())
+//! assert_event!(s, 0, Key::Control.down());
+//! assert_event!(s, 1, Key::Shift.down());
+//! assert_event!(s, 2, Char('t').down());
+//! assert_event!(s, 3, Char('t').up());
+//! assert_event!(s, 4, Key::Shift.up());
+//! assert_event!(s, 5, Key::Control.up());
+//! assert_eq!(s.data.len(), 6);
+//!
+//! // and back
+//! assert_eq!(chord.to_string(), "Ctrl+Shift+t");
+//! ```
+//!
+//! Key names are case insensitive.
+//! Any single character that is not a key name is a [`Char`].
+
+use std::{error::Error, fmt, str::FromStr};
+
+use crate::{
+    common_inputs::{ButtonLike, Char, Key},
+    estimate::{Estimate, Estimation},
+    input_event::SetTo,
+    simulatable::Simulatable,
+    simulator::Simulate,
+};
+
+#[cfg(test)]
+mod test;
+
+/// Final key of a [`Chord`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChordKey {
+    Key(Key),
+    Char(Char),
+}
+
+impl From<Key> for ChordKey {
+    fn from(key: Key) -> Self {
+        ChordKey::Key(key)
+    }
+}
+
+impl From<Char> for ChordKey {
+    fn from(c: Char) -> Self {
+        ChordKey::Char(c)
+    }
+}
+
+impl ChordKey {
+    fn set_to<Smlt>(self, to: bool, simulator: &mut Smlt)
+    where
+        Smlt: Simulate<SetTo<Key, bool>> + Simulate<SetTo<Char, bool>>,
+    {
+        match self {
+            ChordKey::Key(key) => key.set_to(to).run_with(simulator),
+            ChordKey::Char(c) => c.set_to(to).run_with(simulator),
+        }
+    }
+}
+
+impl fmt::Display for ChordKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChordKey::Key(key) => f.write_str(key_name(*key)),
+            ChordKey::Char(Char(c)) => write!(f, "{c}"),
+        }
+    }
+}
+
+/// Keys held down in order while `key` is clicked,
+/// then released in reverse order.
+///
+/// This simulates the same as nesting [`During`](crate::combinator::During)s
+/// with the first held key as the outermost one.
+///
+/// `Display` formats the chord back to a string that can be parsed again.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Chord {
+    pub held: Vec<Key>,
+    pub key: ChordKey,
+}
+
+impl Chord {
+    pub fn new(held: impl IntoIterator<Item = Key>, key: impl Into<ChordKey>) -> Chord {
+        Chord {
+            held: held.into_iter().collect(),
+            key: key.into(),
+        }
+    }
+}
+
+impl<Smlt> Simulatable<Smlt> for Chord
+where
+    Smlt: Simulate<SetTo<Key, bool>> + Simulate<SetTo<Char, bool>>,
+{
+    fn run_with(self, simulator: &mut Smlt) {
+        for key in &self.held {
+            key.down().run_with(simulator);
+        }
+        self.key.set_to(true, simulator);
+        self.key.set_to(false, simulator);
+        for key in self.held.iter().rev() {
+            key.up().run_with(simulator);
+        }
+    }
+}
+
+impl Estimate for Chord {
+    fn estimate(&self) -> Estimation {
+        Estimation::events(self.held.len() * 2 + 2)
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for key in &self.held {
+            write!(f, "{}+", key_name(*key))?;
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+impl FromStr for Chord {
+    type Err = ParseChordError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(ParseChordError::Empty);
+        }
+        let mut parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        // "ctrl + +" and "+" end with two empty parts and mean the plus key
+        let key = match parts[..] {
+            [.., "", ""] => {
+                parts.truncate(parts.len() - 2);
+                "+"
+            }
+            _ => parts.pop().unwrap_or_default(),
+        };
+        let held = parts
+            .into_iter()
+            .map(|name| match parse_chord_key(name)? {
+                ChordKey::Key(key) => Ok(key),
+                ChordKey::Char(Char(c)) => Err(ParseChordError::HeldChar(c)),
+            })
+            .collect::<Result<_, _>>()?;
+        let key = parse_chord_key(key)?;
+        Ok(Chord { held, key })
+    }
+}
+
+/// Error from parsing a [`Chord`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseChordError {
+    /// Nothing to parse
+    Empty,
+    /// There's nothing between two `+`, or around one
+    MissingKey,
+    /// Not a key name and longer than one character
+    UnknownKey(String),
+    /// Only keys can be held, not characters
+    HeldChar(char),
+}
+
+impl fmt::Display for ParseChordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseChordError::Empty => write!(f, "chord is empty"),
+            ParseChordError::MissingKey => write!(f, "chord has an empty key"),
+            ParseChordError::UnknownKey(name) => write!(f, "unknown key name `{name}`"),
+            ParseChordError::HeldChar(c) => {
+                write!(f, "character `{c}` can not be held, only named keys can")
+            }
+        }
+    }
+}
+
+impl Error for ParseChordError {}
+
+/// Parse a key name or a single character.
+pub(crate) fn parse_chord_key(name: &str) -> Result<ChordKey, ParseChordError> {
+    let name = name.trim();
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (None, _) => Err(ParseChordError::MissingKey),
+        (Some(c), None) if c.is_ascii_alphabetic() => {
+            Ok(ChordKey::Char(Char(c.to_ascii_lowercase())))
+        }
+        (Some(c), None) => Ok(ChordKey::Char(Char(c))),
+        _ => key_from_name(name)
+            .map(ChordKey::Key)
+            .ok_or_else(|| ParseChordError::UnknownKey(name.to_owned())),
+    }
+}
+
+/// Look up a key by one of its names, case insensitive.
+pub fn key_from_name(name: &str) -> Option<Key> {
+    let key = match &name.to_ascii_lowercase()[..] {
+        "alt" | "option" | "opt" => Key::Alt,
+        "shift" => Key::Shift,
+        "ctrl" | "control" | "ctl" => Key::Control,
+        "super" | "meta" | "win" | "windows" | "cmd" | "command" => Key::Meta,
+        "f1" => Key::F1,
+        "f2" => Key::F2,
+        "f3" => Key::F3,
+        "f4" => Key::F4,
+        "f5" => Key::F5,
+        "f6" => Key::F6,
+        "f7" => Key::F7,
+        "f8" => Key::F8,
+        "f9" => Key::F9,
+        "f10" => Key::F10,
+        "f11" => Key::F11,
+        "f12" => Key::F12,
+        "capslock" | "caps" => Key::CapsLock,
+        "end" => Key::End,
+        "home" => Key::Home,
+        "pageup" | "pgup" => Key::PageUp,
+        "pagedown" | "pgdn" => Key::PageDown,
+        "escape" | "esc" => Key::Escape,
+        "enter" | "return" => Key::Enter,
+        "space" => Key::Space,
+        "tab" => Key::Tab,
+        "backspace" => Key::Backspace,
+        "delete" | "del" => Key::Delete,
+        "up" | "uparrow" => Key::UpArrow,
+        "down" | "downarrow" => Key::DownArrow,
+        "left" | "leftarrow" => Key::LeftArrow,
+        "right" | "rightarrow" => Key::RightArrow,
+        _ => return None,
+    };
+    Some(key)
+}
+
+/// Canonical name of a key, parsable by [`key_from_name`].
+pub fn key_name(key: Key) -> &'static str {
+    match key {
+        Key::Alt => "Alt",
+        Key::Shift => "Shift",
+        Key::Control => "Ctrl",
+        Key::Meta => "Super",
+        Key::F1 => "F1",
+        Key::F2 => "F2",
+        Key::F3 => "F3",
+        Key::F4 => "F4",
+        Key::F5 => "F5",
+        Key::F6 => "F6",
+        Key::F7 => "F7",
+        Key::F8 => "F8",
+        Key::F9 => "F9",
+        Key::F10 => "F10",
+        Key::F11 => "F11",
+        Key::F12 => "F12",
+        Key::CapsLock => "CapsLock",
+        Key::End => "End",
+        Key::Home => "Home",
+        Key::PageUp => "PageUp",
+        Key::PageDown => "PageDown",
+        Key::Escape => "Esc",
+        Key::Enter => "Enter",
+        Key::Space => "Space",
+        Key::Tab => "Tab",
+        Key::Backspace => "Backspace",
+        Key::Delete => "Delete",
+        Key::UpArrow => "Up",
+        Key::DownArrow => "Down",
+        Key::LeftArrow => "Left",
+        Key::RightArrow => "Right",
+    }
+}
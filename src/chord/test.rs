@@ -0,0 +1,67 @@
+use crate::prelude::*;
+use crate::{assert_event, simulators::string_event_logger::StringEventLogger as S};
+
+use super::{Chord, ChordKey, ParseChordError};
+
+#[test]
+fn chord_parse() {
+    assert_eq!(
+        "ctrl+shift+t".parse(),
+        Ok(Chord::new([Key::Control, Key::Shift], Char('t')))
+    );
+    assert_eq!("alt+F4".parse(), Ok(Chord::new([Key::Alt], Key::F4)));
+    assert_eq!(
+        "super+Left".parse(),
+        Ok(Chord::new([Key::Meta], Key::LeftArrow))
+    );
+    assert_eq!(
+        " Ctrl + A ".parse(),
+        Ok(Chord::new([Key::Control], Char('a')))
+    );
+    assert_eq!("enter".parse(), Ok(Chord::new([], Key::Enter)));
+    assert_eq!("+".parse(), Ok(Chord::new([], Char('+'))));
+    assert_eq!("ctrl++".parse(), Ok(Chord::new([Key::Control], Char('+'))));
+    assert_eq!(
+        " ctrl + + ".parse(),
+        Ok(Chord::new([Key::Control], Char('+')))
+    );
+    assert_eq!(
+        "ctrl + shift+ +".parse(),
+        Ok(Chord::new([Key::Control, Key::Shift], Char('+')))
+    );
+    assert_eq!(" + ".parse(), Ok(Chord::new([], Char('+'))));
+}
+
+#[test]
+fn chord_parse_error() {
+    assert_eq!("".parse::<Chord>(), Err(ParseChordError::Empty));
+    assert_eq!("ctrl+".parse::<Chord>(), Err(ParseChordError::MissingKey));
+    assert_eq!("ctrl + ".parse::<Chord>(), Err(ParseChordError::MissingKey));
+    assert_eq!("++".parse::<Chord>(), Err(ParseChordError::MissingKey));
+    assert_eq!(
+        "ctrl+foo".parse::<Chord>(),
+        Err(ParseChordError::UnknownKey("foo".to_owned()))
+    );
+    assert_eq!("a+b".parse::<Chord>(), Err(ParseChordError::HeldChar('a')));
+}
+
+#[test]
+fn chord_format_round_trip() {
+    for s in ["Ctrl+Shift+t", "Alt+F4", "Super+Left", "Ctrl++", "Esc"] {
+        let chord: Chord = s.parse().unwrap();
+        assert_eq!(chord.to_string(), s);
+    }
+    assert_eq!(ChordKey::from(Key::PageDown).to_string(), "PageDown");
+}
+
+#[test]
+fn chord_run() {
+    let mut s = S::new();
+    let x: Chord = "alt+tab".parse().unwrap();
+    x.run_with(&mut s);
+    assert_event!(s, 0, Key::Alt.down());
+    assert_event!(s, 1, Key::Tab.down());
+    assert_event!(s, 2, Key::Tab.up());
+    assert_event!(s, 3, Key::Alt.up());
+    assert_eq!(s.data.len(), 4);
+}
@@ -1,9 +1,17 @@
 //! Module of helper combinators
 
 use core::fmt;
-use std::{thread, time::Duration};
-
-use crate::{input_event::Invert, simulatable::Simulatable};
+use std::time::{Duration, Instant};
+
+use crate::{
+    common_inputs::{ButtonLike, Key, Modifiers},
+    control,
+    estimate::{Estimate, Estimation},
+    input_event::{Invert, SetTo},
+    query::{Query, Question},
+    simulatable::Simulatable,
+    simulator::Simulate,
+};
 
 #[cfg(test)]
 mod test;
@@ -33,6 +41,61 @@ pub trait Combine: Sized {
         }
     }
 
+    /// Repeat simulation until the simulation is cancelled, see [`control`].
+    /// Without a control this never stops.
+    fn forever(self) -> Forever<Self> {
+        Forever { simulate: self }
+    }
+
+    /// Repeat simulation until duration has passed.
+    /// The time is checked before each repetition so the last one may run over.
+    fn repeat_for(self, duration: Duration) -> RepeatFor<Self> {
+        RepeatFor {
+            duration,
+            simulate: self,
+        }
+    }
+
+    /// Repeat simulation while predicate returns `true`.
+    /// The predicate is given the simulator before each repetition.
+    fn repeat_while<F>(self, predicate: F) -> RepeatWhile<Self, F> {
+        RepeatWhile {
+            predicate,
+            simulate: self,
+        }
+    }
+
+    /// Simulate self only if condition returns `true`
+    /// for the simulator's answer to question, see [`Query`].
+    /// Nothing is simulated if the answer is not known.
+    fn when<Q, F>(self, question: Q, condition: F) -> When<Q, F, Self>
+    where
+        Q: Question,
+        F: FnOnce(Q::Answer) -> bool,
+    {
+        When {
+            question,
+            condition,
+            simulate: self,
+        }
+    }
+
+    /// Simulate self if condition returns `true`
+    /// for the simulator's answer to question, otherwise simulate `otherwise`.
+    /// `otherwise` is simulated if the answer is not known.
+    fn if_else<Q, F, E>(self, question: Q, condition: F, otherwise: E) -> IfElse<Q, F, Self, E>
+    where
+        Q: Question,
+        F: FnOnce(Q::Answer) -> bool,
+    {
+        IfElse {
+            question,
+            condition,
+            then: self,
+            otherwise,
+        }
+    }
+
     /// Iterate through an iterator and simulate each item
     /// Self must be an iterator and its item must be `Simulatable`.
     fn iter_seq(self) -> IterSequence<Self>
@@ -58,11 +121,28 @@ pub trait Combine: Sized {
             simulate: self,
         }
     }
+
+    /// Simulate self while modifier keys are held.
+    /// Modifiers are pressed in the canonical order before self is simulated
+    /// and released in reverse order after.
+    fn with_modifiers(self, modifiers: Modifiers) -> WithModifiers<Self> {
+        WithModifiers {
+            modifiers,
+            simulate: self,
+        }
+    }
 }
 
 impl<S> Combine for S {}
 
 /// Thread sleep for amount of time.
+///
+/// The sleep is passed to the simulator,
+/// real simulators sleep while others may only record or skip it.
+/// Skipped once the simulation is cancelled, see [`control`].
+///
+/// A simulator has to implement `Simulate<Sleep>` for anything that sleeps
+/// to run with it, see [custom simulators](crate#custom-simulators).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Sleep(pub Duration);
 
@@ -72,9 +152,20 @@ impl Sleep {
     }
 }
 
-impl<Smlt> Simulatable<Smlt> for Sleep {
-    fn run_with(self, _: &mut Smlt) {
-        thread::sleep(self.0);
+impl<Smlt> Simulatable<Smlt> for Sleep
+where
+    Smlt: Simulate<Sleep>,
+{
+    fn run_with(self, simulator: &mut Smlt) {
+        if control::checkpoint().is_ok() {
+            simulator.simulate(self)
+        }
+    }
+}
+
+impl Estimate for Sleep {
+    fn estimate(&self) -> Estimation {
+        Estimation::sleep(self.0)
     }
 }
 
@@ -85,14 +176,29 @@ impl fmt::Display for Sleep {
 }
 
 /// Accurate thread sleep for amount of time using [`spin_sleep`](https://crates.io/crates/spin_sleep).
+///
+/// Passed to the simulator like [`Sleep`], which has to implement `Simulate<SpinSleep>`.
+/// Skipped once the simulation is cancelled, see [`control`].
 #[cfg(feature = "spin_sleep")]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct SpinSleep(pub Duration);
 
 #[cfg(feature = "spin_sleep")]
-impl<Smlt> Simulatable<Smlt> for SpinSleep {
-    fn run_with(self, _: &mut Smlt) {
-        spin_sleep::sleep(self.0);
+impl<Smlt> Simulatable<Smlt> for SpinSleep
+where
+    Smlt: Simulate<SpinSleep>,
+{
+    fn run_with(self, simulator: &mut Smlt) {
+        if control::checkpoint().is_ok() {
+            simulator.simulate(self)
+        }
+    }
+}
+
+#[cfg(feature = "spin_sleep")]
+impl Estimate for SpinSleep {
+    fn estimate(&self) -> Estimation {
+        Estimation::sleep(self.0)
     }
 }
 
@@ -104,6 +210,8 @@ impl fmt::Display for SpinSleep {
 }
 
 /// Simulate an input for amount of times
+///
+/// Stops early once the simulation is cancelled, see [`control`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Repeat<S> {
     pub simulate: S,
@@ -116,11 +224,23 @@ where
 {
     fn run_with(self, simulator: &mut Smlt) {
         for _ in 0..self.times {
+            if control::checkpoint().is_err() {
+                return;
+            }
             self.simulate.clone().run_with(simulator)
         }
     }
 }
 
+impl<S> Estimate for Repeat<S>
+where
+    S: Estimate,
+{
+    fn estimate(&self) -> Estimation {
+        self.simulate.estimate().repeat(self.times)
+    }
+}
+
 impl<S> fmt::Display for Repeat<S>
 where
     S: fmt::Display,
@@ -130,9 +250,110 @@ where
     }
 }
 
+/// Simulate an input until the simulation is cancelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Forever<S> {
+    pub simulate: S,
+}
+
+impl<S, Smlt> Simulatable<Smlt> for Forever<S>
+where
+    S: Simulatable<Smlt> + Clone,
+{
+    fn run_with(self, simulator: &mut Smlt) {
+        while control::checkpoint().is_ok() {
+            self.simulate.clone().run_with(simulator)
+        }
+    }
+}
+
+impl<S> fmt::Display for Forever<S>
+where
+    S: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[forever do ({})]", self.simulate)
+    }
+}
+
+/// Simulate an input until an amount of time has passed
+///
+/// The time is real time from the start of the simulation,
+/// simulators that don't sleep repeat it as many times as they can.
+/// Stops early once the simulation is cancelled, see [`control`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RepeatFor<S> {
+    pub simulate: S,
+    pub duration: Duration,
+}
+
+impl<S, Smlt> Simulatable<Smlt> for RepeatFor<S>
+where
+    S: Simulatable<Smlt> + Clone,
+{
+    fn run_with(self, simulator: &mut Smlt) {
+        let start = Instant::now();
+        while start.elapsed() < self.duration {
+            if control::checkpoint().is_err() {
+                return;
+            }
+            self.simulate.clone().run_with(simulator)
+        }
+    }
+}
+
+impl<S> fmt::Display for RepeatFor<S>
+where
+    S: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[for {} ms do ({})]",
+            self.duration.as_millis(),
+            self.simulate
+        )
+    }
+}
+
+/// Simulate an input while a predicate on the simulator returns `true`
+///
+/// Stops early once the simulation is cancelled, see [`control`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RepeatWhile<S, F> {
+    pub simulate: S,
+    pub predicate: F,
+}
+
+impl<S, F, Smlt> Simulatable<Smlt> for RepeatWhile<S, F>
+where
+    S: Simulatable<Smlt> + Clone,
+    F: FnMut(&Smlt) -> bool,
+{
+    fn run_with(mut self, simulator: &mut Smlt) {
+        while (self.predicate)(simulator) {
+            if control::checkpoint().is_err() {
+                return;
+            }
+            self.simulate.clone().run_with(simulator)
+        }
+    }
+}
+
+impl<S, F> fmt::Display for RepeatWhile<S, F>
+where
+    S: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[while predicate do ({})]", self.simulate)
+    }
+}
+
 /// Simulate through a tuple starting from `.0`.
 /// Supported size: 0 <= size <= 32
 /// Nest them if you ever need more.
+///
+/// Stops early once the simulation is cancelled, see [`control`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Sequence<T>(T);
 
@@ -170,12 +391,34 @@ macro_rules! tuple_impl {
                 let inner= self.0;
                 reverse_order!(
                     $(
-                        tuple_impl!(@nth inner, $n).run_with(simulator);
+                        {
+                            if control::checkpoint().is_err() {
+                                return;
+                            }
+                            tuple_impl!(@nth inner, $n).run_with(simulator);
+                        };
                     )*
                 );
             }
         }
 
+        impl<$($g,)*> Estimate for Sequence<($($g,)*)>
+        where
+            $(
+                $g: Estimate,
+            )*
+        {
+            #[allow(unused)]
+            fn estimate(&self) -> Estimation {
+                let inner = &self.0;
+                let mut estimation = Estimation::ZERO;
+                $(
+                    estimation += tuple_impl!(@nth inner, $n).estimate();
+                )*
+                estimation
+            }
+        }
+
         impl<$($g,)*> fmt::Display for Sequence<($($g,)*)>
         where
             $(
@@ -213,6 +456,8 @@ tuple_impl! {
 }
 
 /// Automatically do a for loop on an iterator and simulate for you!
+///
+/// Stops early once the simulation is cancelled, see [`control`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct IterSequence<I> {
     iter: I,
@@ -225,11 +470,25 @@ where
 {
     fn run_with(self, simulator: &mut Smlt) {
         for s in self.iter {
+            if control::checkpoint().is_err() {
+                return;
+            }
             s.run_with(simulator);
         }
     }
 }
 
+/// The iterator is cloned to be iterated.
+impl<I> Estimate for IterSequence<I>
+where
+    I: IntoIterator + Clone,
+    <I as IntoIterator>::Item: Estimate,
+{
+    fn estimate(&self) -> Estimation {
+        self.iter.clone().into_iter().map(|s| s.estimate()).sum()
+    }
+}
+
 impl<S> fmt::Display for IterSequence<S>
 where
     S: fmt::Display,
@@ -258,6 +517,17 @@ where
     }
 }
 
+impl<DS, S> Estimate for During<DS, S>
+where
+    DS: Invert + Estimate + Clone,
+    <DS as Invert>::Output: Estimate,
+    S: Estimate,
+{
+    fn estimate(&self) -> Estimation {
+        self.during.estimate() + self.simulate.estimate() + self.during.clone().invert().estimate()
+    }
+}
+
 impl<DS, S> fmt::Display for During<DS, S>
 where
     DS: fmt::Display,
@@ -267,3 +537,121 @@ where
         write!(f, "[during ({}), do ({})]", self.during, self.simulate)
     }
 }
+
+/// Simulate an input if a condition holds for the answer to a question
+///
+/// The question is asked to the simulator when this is simulated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct When<Q, F, S> {
+    pub question: Q,
+    pub condition: F,
+    pub simulate: S,
+}
+
+impl<Q, F, S, Smlt> Simulatable<Smlt> for When<Q, F, S>
+where
+    Q: Question,
+    F: FnOnce(Q::Answer) -> bool,
+    S: Simulatable<Smlt>,
+    Smlt: Query<Q>,
+{
+    fn run_with(self, simulator: &mut Smlt) {
+        if simulator.query(self.question).is_some_and(self.condition) {
+            self.simulate.run_with(simulator)
+        }
+    }
+}
+
+impl<Q, F, S> fmt::Display for When<Q, F, S>
+where
+    Q: fmt::Display,
+    S: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[when ({}), do ({})]", self.question, self.simulate)
+    }
+}
+
+/// Simulate one of two inputs depending on the answer to a question
+///
+/// The question is asked to the simulator when this is simulated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IfElse<Q, F, T, E> {
+    pub question: Q,
+    pub condition: F,
+    pub then: T,
+    pub otherwise: E,
+}
+
+impl<Q, F, T, E, Smlt> Simulatable<Smlt> for IfElse<Q, F, T, E>
+where
+    Q: Question,
+    F: FnOnce(Q::Answer) -> bool,
+    T: Simulatable<Smlt>,
+    E: Simulatable<Smlt>,
+    Smlt: Query<Q>,
+{
+    fn run_with(self, simulator: &mut Smlt) {
+        if simulator.query(self.question).is_some_and(self.condition) {
+            self.then.run_with(simulator)
+        } else {
+            self.otherwise.run_with(simulator)
+        }
+    }
+}
+
+impl<Q, F, T, E> fmt::Display for IfElse<Q, F, T, E>
+where
+    Q: fmt::Display,
+    T: fmt::Display,
+    E: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[if ({}), do ({}), else do ({})]",
+            self.question, self.then, self.otherwise
+        )
+    }
+}
+
+/// Simulate an input while modifier keys are held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WithModifiers<S> {
+    pub modifiers: Modifiers,
+    pub simulate: S,
+}
+
+impl<S, Smlt> Simulatable<Smlt> for WithModifiers<S>
+where
+    S: Simulatable<Smlt>,
+    SetTo<Key, bool>: Simulatable<Smlt>,
+{
+    fn run_with(self, simulator: &mut Smlt) {
+        for key in self.modifiers.keys() {
+            key.down().run_with(simulator);
+        }
+        self.simulate.run_with(simulator);
+        for key in self.modifiers.keys().rev() {
+            key.up().run_with(simulator);
+        }
+    }
+}
+
+impl<S> Estimate for WithModifiers<S>
+where
+    S: Estimate,
+{
+    fn estimate(&self) -> Estimation {
+        Estimation::events(self.modifiers.keys().count() * 2) + self.simulate.estimate()
+    }
+}
+
+impl<S> fmt::Display for WithModifiers<S>
+where
+    S: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[with ({}), do ({})]", self.modifiers, self.simulate)
+    }
+}
@@ -1,6 +1,11 @@
+use std::thread;
 use std::time::{Duration, Instant};
 
+use crate::common_inputs::mouse_path::Path;
+use crate::control::Control;
+use crate::estimate::{Estimate, Estimation};
 use crate::prelude::*;
+use crate::query::{IsPressed, NoQuery, ScreenSize};
 use crate::{assert_event, simulators::string_event_logger::StringEventLogger as S};
 
 use super::Sleep;
@@ -74,6 +79,111 @@ fn combinator_repeat_complex() {
     assert_eq!(s.data.len(), 8);
 }
 
+#[test]
+fn combinator_repeat_while() {
+    let mut s = S::new();
+    Key::Tab
+        .click()
+        .repeat_while(|s: &S| s.data.len() < 5)
+        .run_with(&mut s);
+    assert_eq!(s.data.len(), 6);
+    assert_event!(s, 5, Key::Tab.up());
+    assert_eq!(
+        Key::Tab
+            .down()
+            .repeat_while(|s: &S| s.data.is_empty())
+            .to_string(),
+        format!("[while predicate do ({})]", Key::Tab.down())
+    );
+}
+
+#[test]
+fn combinator_repeat_for() {
+    let mut s = S::new();
+    let now = Instant::now();
+    MouseButton::Left
+        .click()
+        .sleep_ms(10)
+        .repeat_for(Duration::from_millis(50))
+        .run_with(&mut s);
+    assert!(now.elapsed() >= Duration::from_millis(50));
+    // 5 clicks, or fewer on a slow machine
+    assert!((2..=10).contains(&s.data.len()));
+    assert_eq!(s.data.len() % 2, 0);
+}
+
+#[test]
+fn combinator_forever() {
+    let control = Control::new();
+    let handle = control.clone();
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(30));
+        handle.cancel();
+    });
+    let mut s = S::new();
+    let result = control.run_with(MouseButton::Left.click().sleep_ms(5).forever(), &mut s);
+    assert!(result.is_err());
+    assert!(!s.data.is_empty());
+    assert_eq!(s.data.len() % 2, 0);
+    assert_eq!(
+        MouseButton::Left.down().forever().to_string(),
+        format!("[forever do ({})]", MouseButton::Left.down())
+    );
+}
+
+#[test]
+fn combinator_when_if_else() {
+    let mut s = S::new().tracked();
+    let press_if_released = |key: Key| key.down().when(IsPressed(key), |pressed| !pressed);
+    press_if_released(Key::Shift).run_with(&mut s);
+    press_if_released(Key::Shift).run_with(&mut s);
+    assert_eq!(s.inner.data.len(), 1);
+
+    let release_or_press = || {
+        Key::Alt
+            .up()
+            .if_else(IsPressed(Key::Alt), |pressed| pressed, Key::Alt.down())
+    };
+    release_or_press().run_with(&mut s);
+    release_or_press().run_with(&mut s);
+    let logger = s.inner;
+    assert_event!(logger, 1, Key::Alt.down());
+    assert_event!(logger, 2, Key::Alt.up());
+
+    // unknown answers are false
+    let mut s = S::new().layer(NoQuery);
+    Key::Tab
+        .click()
+        .if_else(MousePosition, |_| true, Key::Space.click())
+        .run_with(&mut s);
+    let logger = s.inner;
+    assert_event!(logger, 0, Key::Space.down());
+
+    // simulators without the answer say so too
+    let mut s = S::new();
+    Key::Tab.click().when(ScreenSize, |_| true).run_with(&mut s);
+    assert!(s.data.is_empty());
+
+    assert_eq!(
+        Key::Tab
+            .down()
+            .when(IsPressed(Key::Shift), |pressed| pressed)
+            .to_string(),
+        format!("[when (is Shift pressed), do ({})]", Key::Tab.down())
+    );
+    assert_eq!(
+        Key::Tab
+            .down()
+            .if_else(ScreenSize, |(w, _)| w > 800, Key::Space.down())
+            .to_string(),
+        format!(
+            "[if (screen size), do ({}), else do ({})]",
+            Key::Tab.down(),
+            Key::Space.down()
+        )
+    );
+}
+
 #[test]
 fn combinator_iter_seq() {
     let mut s = S::new();
@@ -142,3 +252,72 @@ fn combinator_during_nested() {
     assert_event!(s, 5, Key::F1.down());
     assert_eq!(s.data.len(), 6);
 }
+
+#[test]
+fn combinator_with_modifiers() {
+    let mut s = S::new();
+    let x = Key::Tab
+        .click()
+        .with_modifiers(Modifiers::ALT | Modifiers::SHIFT | Modifiers::CONTROL);
+    x.run_with(&mut s);
+    assert_event!(s, 0, Key::Control.down());
+    assert_event!(s, 1, Key::Shift.down());
+    assert_event!(s, 2, Key::Alt.down());
+    assert_event!(s, 3, Key::Tab.down());
+    assert_event!(s, 4, Key::Tab.up());
+    assert_event!(s, 5, Key::Alt.up());
+    assert_event!(s, 6, Key::Shift.up());
+    assert_event!(s, 7, Key::Control.up());
+    assert_eq!(s.data.len(), 8);
+}
+
+#[test]
+fn combinator_with_modifiers_display() {
+    let x = Key::Tab
+        .down()
+        .with_modifiers(Modifiers::SHIFT | Modifiers::CONTROL);
+    assert_eq!(x.to_string(), "[with (Ctrl+Shift), do ([set Tab to true])]");
+    assert_eq!(Modifiers::NONE.to_string(), "");
+    assert_eq!(Modifiers::from_key(Key::Meta), Some(Modifiers::META));
+    assert_eq!(Modifiers::from_key(Key::Tab), None);
+}
+
+#[test]
+fn combinator_estimate() {
+    let x = (
+        Key::Tab.click().with_modifiers(Modifiers::ALT),
+        [Char('a').click(), Char('b').click()].iter_seq(),
+        Sleep::from_ms(10),
+    )
+        .seq()
+        .during(Key::Shift.down())
+        .repeat(3);
+    assert_eq!(
+        x.estimate(),
+        Estimation {
+            duration: Duration::from_millis(30),
+            events: 30,
+        }
+    );
+
+    let mut s = S::new();
+    x.run_with(&mut s);
+    assert_eq!(s.data.len(), x.estimate().events);
+}
+
+#[test]
+fn combinator_estimate_mouse_move() {
+    let x = MousePosition
+        .move_along(Path::linear((0, 0), (100, 0)))
+        .duration_ms(100)
+        .rate(50);
+    assert_eq!(
+        x.estimate(),
+        Estimation {
+            duration: Duration::from_millis(100),
+            events: 6,
+        }
+    );
+    assert_eq!(x.clone().relative().estimate().events, 5);
+    assert_eq!(MouseButton::Left.drag_along(x).estimate().events, 8);
+}
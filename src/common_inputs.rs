@@ -3,9 +3,21 @@
 //! This is currently pretty much incomplete.
 //! Prefer `enigo` and their `Key` and `MouseButton` for most cases.
 
-use crate::{combinator::*, input_event::*};
+use crate::{
+    combinator::*,
+    estimate::{Estimate, Estimation},
+    input_event::*,
+    simulatable::Simulatable,
+    simulator::Simulate,
+};
 use std::fmt;
 
+pub mod mouse_path;
+pub mod screen;
+
+#[cfg(test)]
+mod test;
+
 /// The whole thing is a convenience shorthand
 pub trait ButtonLike: Sized {
     /// Set this button state
@@ -64,14 +76,106 @@ pub trait ButtonLike: Sized {
     }
 }
 
+/// Convenience shorthands for buttons on a mouse.
+pub trait MouseButtonLike: ButtonLike {
+    /// Press the button at `from`, move the mouse smoothly to `to` and release.
+    /// The button is always released, see [`Drag`].
+    fn drag(self, from: (i32, i32), to: (i32, i32)) -> Drag<Self> {
+        self.drag_along(MousePosition.move_along(mouse_path::Path::linear(from, to)))
+    }
+
+    /// Press the button at the start of `movement`, do the movement and release.
+    /// The button is always released, see [`Drag`].
+    fn drag_along(self, movement: mouse_path::MouseMove) -> Drag<Self> {
+        Drag {
+            button: self,
+            movement,
+        }
+    }
+}
+
+/// Drag and drop with a mouse button.
+///
+/// Moves the mouse to the start of `movement` unless it is relative,
+/// presses `button`, does the movement and then releases `button`.
+/// The release is guaranteed to be simulated even if a step in between panics.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Drag<B> {
+    pub button: B,
+    pub movement: mouse_path::MouseMove,
+}
+
+/// Releases the button when dropped, panic or not.
+struct ReleaseGuard<'a, B, Smlt>
+where
+    B: ButtonLike,
+    SetTo<B, bool>: Simulatable<Smlt>,
+{
+    button: Option<B>,
+    simulator: &'a mut Smlt,
+}
+
+impl<'a, B, Smlt> Drop for ReleaseGuard<'a, B, Smlt>
+where
+    B: ButtonLike,
+    SetTo<B, bool>: Simulatable<Smlt>,
+{
+    fn drop(&mut self) {
+        if let Some(button) = self.button.take() {
+            button.up().run_with(self.simulator);
+        }
+    }
+}
+
+impl<B, Smlt> Simulatable<Smlt> for Drag<B>
+where
+    B: ButtonLike + Clone,
+    Smlt:
+        Simulate<SetTo<MousePosition, (i32, i32)>> + Simulate<ChangeBy<MousePosition, (i32, i32)>>,
+    SetTo<B, bool>: Simulatable<Smlt>,
+    Sleep: Simulatable<Smlt>,
+{
+    fn run_with(self, simulator: &mut Smlt) {
+        let Drag { button, movement } = self;
+        if movement.moves_to_start() {
+            let (x, y) = movement.path.start();
+            MousePosition.move_to(x, y).run_with(simulator);
+        }
+        button.clone().down().run_with(simulator);
+        let guard = ReleaseGuard {
+            button: Some(button),
+            simulator,
+        };
+        movement.run_steps(guard.simulator);
+    }
+}
+
+impl<B> Estimate for Drag<B> {
+    fn estimate(&self) -> Estimation {
+        Estimation::events(2) + self.movement.estimate()
+    }
+}
+
+impl<B> fmt::Display for Drag<B>
+where
+    B: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[drag {} along ({})]", self.button, self.movement)
+    }
+}
+
 #[rustfmt::skip]
 #[non_exhaustive]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Key {
     /// alt key on Linux and Windows (option key on macOS)
     Alt,
     Shift,
     Control,
+    /// super key on Linux, windows key on Windows, command key on macOS
+    Meta,
 
     F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
 
@@ -94,7 +198,131 @@ impl fmt::Display for Key {
     }
 }
 
+/// Set of modifier keys.
+///
+/// Keys are always iterated in the canonical order:
+/// Control, Shift, Alt, Meta.
+///
+/// ```
+/// # use kemuler::prelude::*;
+/// let mods = Modifiers::SHIFT | Modifiers::CONTROL;
+/// assert!(mods.contains(Modifiers::CONTROL));
+/// assert_eq!(mods.keys().collect::<Vec<_>>(), [Key::Control, Key::Shift]);
+/// assert_eq!(mods.to_string(), "Ctrl+Shift");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Modifiers(u8);
+
+impl Modifiers {
+    pub const NONE: Modifiers = Modifiers(0);
+    pub const CONTROL: Modifiers = Modifiers(1 << 0);
+    pub const SHIFT: Modifiers = Modifiers(1 << 1);
+    pub const ALT: Modifiers = Modifiers(1 << 2);
+    pub const META: Modifiers = Modifiers(1 << 3);
+    pub const ALL: Modifiers = Modifiers(0b1111);
+
+    /// Canonical order
+    const KEYS: [(Modifiers, Key); 4] = [
+        (Modifiers::CONTROL, Key::Control),
+        (Modifiers::SHIFT, Key::Shift),
+        (Modifiers::ALT, Key::Alt),
+        (Modifiers::META, Key::Meta),
+    ];
+
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Unknown bits are dropped.
+    pub const fn from_bits_truncate(bits: u8) -> Modifiers {
+        Modifiers(bits & Modifiers::ALL.0)
+    }
+
+    /// Modifier of a key, `None` if the key is not a modifier.
+    pub fn from_key(key: Key) -> Option<Modifiers> {
+        Modifiers::KEYS
+            .iter()
+            .find(|(_, k)| *k == key)
+            .map(|(modifier, _)| *modifier)
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether all modifiers in `other` are also in `self`.
+    pub const fn contains(self, other: Modifiers) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn insert(&mut self, other: Modifiers) {
+        self.0 |= other.0;
+    }
+
+    pub fn remove(&mut self, other: Modifiers) {
+        self.0 &= !other.0;
+    }
+
+    /// Keys in this set in the canonical order.
+    pub fn keys(self) -> impl DoubleEndedIterator<Item = Key> {
+        Modifiers::KEYS
+            .into_iter()
+            .filter(move |(modifier, _)| self.contains(*modifier))
+            .map(|(_, key)| key)
+    }
+}
+
+impl std::ops::BitOr for Modifiers {
+    type Output = Modifiers;
+
+    fn bitor(self, rhs: Modifiers) -> Modifiers {
+        Modifiers(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitOrAssign for Modifiers {
+    fn bitor_assign(&mut self, rhs: Modifiers) {
+        self.insert(rhs)
+    }
+}
+
+impl std::ops::BitAnd for Modifiers {
+    type Output = Modifiers;
+
+    fn bitand(self, rhs: Modifiers) -> Modifiers {
+        Modifiers(self.0 & rhs.0)
+    }
+}
+
+impl std::ops::Sub for Modifiers {
+    type Output = Modifiers;
+
+    fn sub(self, rhs: Modifiers) -> Modifiers {
+        Modifiers(self.0 & !rhs.0)
+    }
+}
+
+impl FromIterator<Modifiers> for Modifiers {
+    fn from_iter<T: IntoIterator<Item = Modifiers>>(iter: T) -> Self {
+        iter.into_iter().fold(Modifiers::NONE, |a, b| a | b)
+    }
+}
+
+impl fmt::Display for Modifiers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, key) in self.keys().enumerate() {
+            if i != 0 {
+                write!(f, "+")?;
+            }
+            write!(f, "{}", crate::chord::key_name(key))?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MousePosition;
 
 impl MousePosition {
@@ -135,6 +363,24 @@ impl MousePosition {
             by: (x, y),
         }
     }
+
+    /// Move mouse smoothly along a path.
+    /// See [`mouse_path`] for more.
+    pub fn move_along(self, path: mouse_path::Path) -> mouse_path::MouseMove {
+        mouse_path::MouseMove::new(path)
+    }
+
+    /// Move mouse to a position relative to the screen.
+    /// See [`screen`] for more.
+    pub fn move_to_screen<P: screen::ScreenPoint>(self, to: P) -> screen::ScreenMove<P> {
+        screen::ScreenMove::new(to)
+    }
+
+    /// Mouse position on a monitor of the display layout.
+    /// See [`screen`] for more.
+    pub fn on_monitor(self, index: usize) -> screen::OnMonitor {
+        screen::OnMonitor(index)
+    }
 }
 
 impl fmt::Display for MousePosition {
@@ -144,6 +390,7 @@ impl fmt::Display for MousePosition {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MouseScroll;
 
 impl MouseScroll {
@@ -174,6 +421,7 @@ impl fmt::Display for MouseScroll {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub enum MouseButton {
     Left,
@@ -183,6 +431,8 @@ pub enum MouseButton {
 
 impl ButtonLike for MouseButton {}
 
+impl MouseButtonLike for MouseButton {}
+
 impl fmt::Display for MouseButton {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{self:?}")
@@ -190,10 +440,17 @@ impl fmt::Display for MouseButton {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Char(pub char);
 
 impl ButtonLike for Char {}
 
+impl fmt::Display for Char {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct StrSequence<'a>(pub &'a str);
 
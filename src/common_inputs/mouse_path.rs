@@ -0,0 +1,416 @@
+//! Smooth mouse movement along paths.
+//!
+//! Instead of teleporting the cursor with [`MousePosition::move_to`],
+//! a [`MouseMove`] walks the cursor along a [`Path`] in many small steps
+//! with sleeps in between, shaped by an [`Easing`] function.
+//!
+//! ```
+//! # use kemuler::simulators::string_event_logger::StringEventLogger as Simulator;
+//! # use kemuler::assert_event;
+//! use kemuler::prelude::*;
+//! use kemuler::common_inputs::mouse_path::{Easing, Path};
+//!
+//! let mut s = Simulator::new();
+//!
+//! MousePosition
+//!     .move_along(Path::linear((0, 0), (100, 0)))
+//!     .duration_ms(4)
+//!     .rate(1000)
+//!     .easing(Easing::Linear)
+//!     .run_with(&mut s);
+//!
+//! assert_event!(s, 0, MousePosition.move_to(0, 0));
+//! assert_event!(s, 1, MousePosition.move_to(25, 0));
+//! assert_event!(s, 2, MousePosition.move_to(50, 0));
+//! assert_event!(s, 3, MousePosition.move_to(75, 0));
+//! assert_event!(s, 4, MousePosition.move_to(100, 0));
+//! assert_eq!(s.data.len(), 5);
+//! ```
+
+use std::{f64::consts::TAU, fmt, time::Duration};
+
+use crate::{
+    combinator::Sleep,
+    common_inputs::MousePosition,
+    estimate::{Estimate, Estimation},
+    input_event::*,
+    simulatable::Simulatable,
+    simulator::Simulate,
+};
+
+/// Easing function that maps linear progress to eased progress.
+/// Both are in range `0.0..=1.0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Easing {
+    /// Constant speed.
+    #[default]
+    Linear,
+    /// Start slow and speed up (cubic).
+    EaseIn,
+    /// Start fast and slow down (cubic).
+    EaseOut,
+    /// Slow at both ends, fast in the middle (cubic).
+    EaseInOut,
+    /// Slow at both ends, fast in the middle (sine).
+    /// Gentler than `EaseInOut`.
+    Sine,
+}
+
+impl Easing {
+    /// Map progress `t` to eased progress.
+    /// `t` is clamped to `0.0..=1.0`.
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Easing::Sine => -((std::f64::consts::PI * t).cos() - 1.0) / 2.0,
+        }
+    }
+}
+
+impl fmt::Display for Easing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// Shape the cursor travels along.
+/// All coordinates are in pixels.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Path {
+    /// Straight line.
+    Linear { from: (i32, i32), to: (i32, i32) },
+    /// Bézier curve of any degree.
+    /// The first and last point are the start and the end,
+    /// every point in between is a control point.
+    /// A move along a curve without points does nothing.
+    Bezier { points: Vec<(i32, i32)> },
+    /// Arc around `center`, angles are in radians.
+    /// A sweep of `TAU` is a full circle.
+    Arc {
+        center: (i32, i32),
+        radius: f64,
+        start_angle: f64,
+        sweep: f64,
+    },
+    /// Slightly curved line that looks like it was done by a human hand.
+    /// The curve is generated from `seed` so the same seed
+    /// always produces the same path.
+    Human {
+        from: (i32, i32),
+        to: (i32, i32),
+        seed: u64,
+    },
+}
+
+impl Path {
+    /// Straight line from `from` to `to`.
+    pub fn linear(from: (i32, i32), to: (i32, i32)) -> Path {
+        Path::Linear { from, to }
+    }
+
+    /// Bézier curve through `points`.
+    ///
+    /// # Panics
+    /// Panics if `points` is empty.
+    pub fn bezier(points: impl IntoIterator<Item = (i32, i32)>) -> Path {
+        let points: Vec<_> = points.into_iter().collect();
+        assert!(
+            !points.is_empty(),
+            "bezier path requires at least one point"
+        );
+        Path::Bezier { points }
+    }
+
+    /// Arc around `center` starting at `start_angle` and sweeping by `sweep`.
+    /// Angles are in radians; positive sweep goes clockwise on screen.
+    pub fn arc(center: (i32, i32), radius: f64, start_angle: f64, sweep: f64) -> Path {
+        Path::Arc {
+            center,
+            radius,
+            start_angle,
+            sweep,
+        }
+    }
+
+    /// Full circle around `center` starting and ending at its right side.
+    pub fn circle(center: (i32, i32), radius: f64) -> Path {
+        Path::arc(center, radius, 0.0, TAU)
+    }
+
+    /// Human-like curved line from `from` to `to`.
+    pub fn human(from: (i32, i32), to: (i32, i32), seed: u64) -> Path {
+        Path::Human { from, to, seed }
+    }
+
+    /// Point on the path at progress `t` in `0.0..=1.0`.
+    /// A Bézier curve without points is at `(0, 0)`.
+    pub fn point_at(&self, t: f64) -> (f64, f64) {
+        match self {
+            Path::Linear { from, to } => lerp(to_f64(*from), to_f64(*to), t),
+            Path::Bezier { points } => {
+                let points: Vec<_> = points.iter().copied().map(to_f64).collect();
+                de_casteljau(points, t)
+            }
+            Path::Arc {
+                center,
+                radius,
+                start_angle,
+                sweep,
+            } => {
+                let angle = start_angle + sweep * t;
+                (
+                    center.0 as f64 + angle.cos() * radius,
+                    center.1 as f64 + angle.sin() * radius,
+                )
+            }
+            Path::Human { from, to, seed } => {
+                de_casteljau(human_control_points(*from, *to, *seed).to_vec(), t)
+            }
+        }
+    }
+
+    /// Where this path starts.
+    pub fn start(&self) -> (i32, i32) {
+        round(self.point_at(0.0))
+    }
+
+    /// Where this path ends.
+    pub fn end(&self) -> (i32, i32) {
+        round(self.point_at(1.0))
+    }
+
+    /// A Bézier curve without points.
+    fn is_empty(&self) -> bool {
+        matches!(self, Path::Bezier { points } if points.is_empty())
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Path::Linear { from, to } => write!(f, "line {from:?} -> {to:?}"),
+            Path::Bezier { points } => write!(f, "bezier {points:?}"),
+            Path::Arc {
+                center,
+                radius,
+                start_angle,
+                sweep,
+            } => write!(
+                f,
+                "arc around {center:?} with radius {radius} from {start_angle} rad by {sweep} rad"
+            ),
+            Path::Human { from, to, .. } => write!(f, "human line {from:?} -> {to:?}"),
+        }
+    }
+}
+
+fn to_f64(p: (i32, i32)) -> (f64, f64) {
+    (p.0 as f64, p.1 as f64)
+}
+
+fn round(p: (f64, f64)) -> (i32, i32) {
+    (p.0.round() as i32, p.1.round() as i32)
+}
+
+fn lerp(a: (f64, f64), b: (f64, f64), t: f64) -> (f64, f64) {
+    (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t)
+}
+
+fn de_casteljau(mut points: Vec<(f64, f64)>, t: f64) -> (f64, f64) {
+    for len in (1..points.len()).rev() {
+        for i in 0..len {
+            points[i] = lerp(points[i], points[i + 1], t);
+        }
+    }
+    points.first().copied().unwrap_or_default()
+}
+
+/// xorshift64*, good enough for wiggling a cursor
+fn next_random(state: &mut u64) -> f64 {
+    *state ^= *state >> 12;
+    *state ^= *state << 25;
+    *state ^= *state >> 27;
+    let x = state.wrapping_mul(0x2545_F491_4F6C_DD1D);
+    (x >> 11) as f64 / (1u64 << 53) as f64
+}
+
+fn human_control_points(from: (i32, i32), to: (i32, i32), seed: u64) -> [(f64, f64); 4] {
+    // zero is a fixed point of xorshift
+    let mut state = seed ^ 0x9E37_79B9_7F4A_7C15;
+    let from = to_f64(from);
+    let to = to_f64(to);
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    // perpendicular to the line, same length as the line
+    let normal = (-dy, dx);
+    let mut control = |along: f64| {
+        let along = along + (next_random(&mut state) - 0.5) * 0.2;
+        let away = (next_random(&mut state) - 0.5) * 0.4;
+        let p = lerp(from, to, along);
+        (p.0 + normal.0 * away, p.1 + normal.1 * away)
+    };
+    let c1 = control(1.0 / 3.0);
+    let c2 = control(2.0 / 3.0);
+    [from, c1, c2, to]
+}
+
+/// Move the mouse along a [`Path`] in small steps over a duration.
+///
+/// In absolute mode (the default) the cursor is set to the start of the path
+/// and then to every following point with `SetTo<MousePosition, (i32, i32)>`.
+/// In relative mode the path is walked from wherever the cursor is
+/// with `ChangeBy<MousePosition, (i32, i32)>`.
+/// Every step is preceded by a [`Sleep`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MouseMove {
+    pub path: Path,
+    pub easing: Easing,
+    pub duration: Duration,
+    /// Steps per second
+    pub rate: u32,
+    pub relative: bool,
+}
+
+impl MouseMove {
+    /// Move along `path` for 250 ms at 60 steps per second without easing.
+    pub fn new(path: Path) -> MouseMove {
+        MouseMove {
+            path,
+            easing: Easing::Linear,
+            duration: Duration::from_millis(250),
+            rate: 60,
+            relative: false,
+        }
+    }
+
+    /// Take this long to finish the path.
+    pub fn duration(mut self, duration: Duration) -> MouseMove {
+        self.duration = duration;
+        self
+    }
+
+    /// Take this long in milliseconds to finish the path.
+    pub fn duration_ms(self, duration: u64) -> MouseMove {
+        self.duration(Duration::from_millis(duration))
+    }
+
+    /// Amount of steps per second.
+    pub fn rate(mut self, rate: u32) -> MouseMove {
+        self.rate = rate;
+        self
+    }
+
+    pub fn easing(mut self, easing: Easing) -> MouseMove {
+        self.easing = easing;
+        self
+    }
+
+    /// Walk the path relative to the current cursor position.
+    pub fn relative(mut self) -> MouseMove {
+        self.relative = true;
+        self
+    }
+
+    /// Amount of steps this movement is split into, at least one.
+    pub fn steps(&self) -> u32 {
+        let steps = (self.duration.as_secs_f64() * self.rate as f64).round();
+        (steps as u32).max(1)
+    }
+
+    /// Sleep duration before each step.
+    pub fn step_duration(&self) -> Duration {
+        self.duration / self.steps()
+    }
+
+    /// Cursor position after each step, without the start of the path.
+    /// Empty for a path without points.
+    pub fn points(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        let steps = self.steps();
+        let count = if self.path.is_empty() { 0 } else { steps };
+        (1..=count).map(move |i| {
+            let t = self.easing.apply(i as f64 / steps as f64);
+            round(self.path.point_at(t))
+        })
+    }
+}
+
+impl<Smlt> Simulatable<Smlt> for MouseMove
+where
+    Smlt:
+        Simulate<SetTo<MousePosition, (i32, i32)>> + Simulate<ChangeBy<MousePosition, (i32, i32)>>,
+    Sleep: Simulatable<Smlt>,
+{
+    fn run_with(self, simulator: &mut Smlt) {
+        if self.moves_to_start() {
+            let (x, y) = self.path.start();
+            MousePosition.move_to(x, y).run_with(simulator);
+        }
+        self.run_steps(simulator);
+    }
+}
+
+impl MouseMove {
+    /// The cursor is set to the start of the path before the steps.
+    pub(crate) fn moves_to_start(&self) -> bool {
+        !self.relative && !self.path.is_empty()
+    }
+
+    /// Every step after the start of the path.
+    pub(crate) fn run_steps<Smlt>(&self, simulator: &mut Smlt)
+    where
+        Smlt: Simulate<SetTo<MousePosition, (i32, i32)>>
+            + Simulate<ChangeBy<MousePosition, (i32, i32)>>,
+        Sleep: Simulatable<Smlt>,
+    {
+        let step_duration = self.step_duration();
+        let mut previous = self.path.start();
+        for point in self.points() {
+            Sleep(step_duration).run_with(simulator);
+            if self.relative {
+                MousePosition
+                    .move_by(point.0 - previous.0, point.1 - previous.1)
+                    .run_with(simulator);
+            } else {
+                MousePosition.move_to(point.0, point.1).run_with(simulator);
+            }
+            previous = point;
+        }
+    }
+}
+
+impl Estimate for MouseMove {
+    fn estimate(&self) -> Estimation {
+        if self.path.is_empty() {
+            return Estimation::ZERO;
+        }
+        let start = if self.relative { 0 } else { 1 };
+        let step = Estimation::sleep(self.step_duration()) + Estimation::EVENT;
+        Estimation::events(start) + step.repeat(self.steps() as usize)
+    }
+}
+
+impl fmt::Display for MouseMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[move {} along {} in {} ms with {} easing]",
+            if self.relative {
+                "mouse relatively"
+            } else {
+                "mouse"
+            },
+            self.path,
+            self.duration.as_millis(),
+            self.easing,
+        )
+    }
+}
@@ -0,0 +1,414 @@
+//! Mouse positions relative to the screen.
+//!
+//! [`MousePosition::move_to`] takes pixels, which only fit one resolution.
+//! A [`ScreenMove`] takes a [`ScreenPoint`] instead,
+//! such as [`Normalized`], [`Percent`] or an [`Anchor`],
+//! and converts it to pixels when it's simulated
+//! using the screen size answered by the simulator, see [`query`](crate::query).
+//!
+//! With more than one monitor, [`ScreenMove::on_monitor`] targets one monitor
+//! of the [`DisplayLayout`] answered by the simulator,
+//! the position is converted to global pixels before it's simulated.
+//! [`MousePosition::on_monitor`] does the same for pixels relative to a monitor.
+//! Mouse events themselves, such as [`MousePosition::move_to`], are always in global pixels.
+//!
+//! [`ScreenSized`] answers a configured screen size or display layout
+//! for simulators that can't.
+//!
+//! ```
+//! # use kemuler::simulators::string_event_logger::StringEventLogger as Simulator;
+//! # use kemuler::assert_event;
+//! use kemuler::prelude::*;
+//! use kemuler::common_inputs::screen::{
+//!     Anchor, DisplayLayout, Monitor, Normalized, Percent, ScreenSized,
+//! };
+//!
+//! let mut s = Simulator::new().layer(ScreenSized::new(1920, 1080));
+//!
+//! MousePosition.move_to_screen(Anchor::Center).run_with(&mut s);
+//! MousePosition.move_to_screen(Normalized(0.25, 0.0)).run_with(&mut s);
+//! MousePosition.move_to_screen(Percent(100.0, 100.0)).run_with(&mut s);
+//! MousePosition.move_to_screen(Anchor::TopRight.offset(-10, 10)).run_with(&mut s);
+//!
+//! let s = s.inner;
+//! assert_event!(s, 0, MousePosition.move_to(960, 540));
+//! assert_event!(s, 1, MousePosition.move_to(480, 0));
+//! assert_event!(s, 2, MousePosition.move_to(1919, 1079));
+//! assert_event!(s, 3, MousePosition.move_to(1909, 10));
+//!
+//! // a second monitor on the right of the first one
+//! let layout = DisplayLayout::single(1920, 1080).with_monitor(Monitor::new(1920, 0, 1280, 1024));
+//! let mut s = Simulator::new().layer(ScreenSized::with_layout(layout));
+//! MousePosition.move_to_screen(Anchor::Center).on_monitor(1).run_with(&mut s);
+//! MousePosition.on_monitor(1).move_to(100, 0).run_with(&mut s);
+//!
+//! let s = s.inner;
+//! assert_event!(s, 0, MousePosition.move_to(2560, 512));
+//! assert_event!(s, 1, MousePosition.move_to(2020, 0));
+//! ```
+
+use std::fmt;
+
+use crate::{
+    common_inputs::MousePosition,
+    estimate::{Estimate, Estimation},
+    input_event::*,
+    query::{Displays, Query, ScreenSize},
+    simulatable::Simulatable,
+    simulator::{Layer, Simulate},
+};
+
+/// A position on the screen that can be converted to pixels.
+pub trait ScreenPoint {
+    /// Pixel position on a screen of `size`.
+    fn to_pixels(&self, size: (u32, u32)) -> (i32, i32);
+}
+
+/// Pixels, the same on every screen.
+impl ScreenPoint for (i32, i32) {
+    fn to_pixels(&self, _: (u32, u32)) -> (i32, i32) {
+        *self
+    }
+}
+
+/// Position where `(0.0, 0.0)` is the top left pixel
+/// and `(1.0, 1.0)` is the bottom right pixel.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Normalized(pub f64, pub f64);
+
+impl ScreenPoint for Normalized {
+    fn to_pixels(&self, (width, height): (u32, u32)) -> (i32, i32) {
+        let scale = |t: f64, length: u32| (t * length.saturating_sub(1) as f64).round() as i32;
+        (scale(self.0, width), scale(self.1, height))
+    }
+}
+
+impl fmt::Display for Normalized {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.0, self.1)
+    }
+}
+
+/// Same as [`Normalized`] but from `0.0` to `100.0`.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Percent(pub f64, pub f64);
+
+impl From<Percent> for Normalized {
+    fn from(percent: Percent) -> Self {
+        Normalized(percent.0 / 100.0, percent.1 / 100.0)
+    }
+}
+
+impl ScreenPoint for Percent {
+    fn to_pixels(&self, size: (u32, u32)) -> (i32, i32) {
+        Normalized::from(*self).to_pixels(size)
+    }
+}
+
+impl fmt::Display for Percent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}%, {}%)", self.0, self.1)
+    }
+}
+
+/// Corners, middle of edges and center of the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Position `(x, y)` pixels away from this anchor.
+    pub fn offset(self, x: i32, y: i32) -> Anchored {
+        Anchored {
+            anchor: self,
+            offset: (x, y),
+        }
+    }
+}
+
+impl From<Anchor> for Normalized {
+    fn from(anchor: Anchor) -> Self {
+        match anchor {
+            Anchor::TopLeft => Normalized(0.0, 0.0),
+            Anchor::Top => Normalized(0.5, 0.0),
+            Anchor::TopRight => Normalized(1.0, 0.0),
+            Anchor::Left => Normalized(0.0, 0.5),
+            Anchor::Center => Normalized(0.5, 0.5),
+            Anchor::Right => Normalized(1.0, 0.5),
+            Anchor::BottomLeft => Normalized(0.0, 1.0),
+            Anchor::Bottom => Normalized(0.5, 1.0),
+            Anchor::BottomRight => Normalized(1.0, 1.0),
+        }
+    }
+}
+
+impl ScreenPoint for Anchor {
+    fn to_pixels(&self, size: (u32, u32)) -> (i32, i32) {
+        Normalized::from(*self).to_pixels(size)
+    }
+}
+
+impl fmt::Display for Anchor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Anchor::TopLeft => "top left",
+            Anchor::Top => "top",
+            Anchor::TopRight => "top right",
+            Anchor::Left => "left",
+            Anchor::Center => "center",
+            Anchor::Right => "right",
+            Anchor::BottomLeft => "bottom left",
+            Anchor::Bottom => "bottom",
+            Anchor::BottomRight => "bottom right",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Position in pixels away from an anchor.
+/// Created by [`Anchor::offset`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Anchored {
+    pub anchor: Anchor,
+    pub offset: (i32, i32),
+}
+
+impl ScreenPoint for Anchored {
+    fn to_pixels(&self, size: (u32, u32)) -> (i32, i32) {
+        let (x, y) = self.anchor.to_pixels(size);
+        (
+            x.saturating_add(self.offset.0),
+            y.saturating_add(self.offset.1),
+        )
+    }
+}
+
+impl fmt::Display for Anchored {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} + {:?}", self.anchor, self.offset)
+    }
+}
+
+/// A monitor of a [`DisplayLayout`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Monitor {
+    /// Global pixel position of the top left pixel
+    pub origin: (i32, i32),
+    /// Size in pixels
+    pub size: (u32, u32),
+}
+
+impl Monitor {
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Monitor {
+        Monitor {
+            origin: (x, y),
+            size: (width, height),
+        }
+    }
+
+    /// Global pixel position of a point on this monitor.
+    pub fn to_global(&self, point: &impl ScreenPoint) -> (i32, i32) {
+        let (x, y) = point.to_pixels(self.size);
+        (
+            self.origin.0.saturating_add(x),
+            self.origin.1.saturating_add(y),
+        )
+    }
+
+    /// Is a global pixel position on this monitor.
+    pub fn contains(&self, (x, y): (i32, i32)) -> bool {
+        let (left, top) = self.origin;
+        let (width, height) = self.size;
+        (left..left.saturating_add_unsigned(width)).contains(&x)
+            && (top..top.saturating_add_unsigned(height)).contains(&y)
+    }
+}
+
+impl fmt::Display for Monitor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (x, y) = self.origin;
+        let (width, height) = self.size;
+        write!(f, "{width}x{height} at ({x}, {y})")
+    }
+}
+
+/// Monitors and where they are in the global pixel space.
+///
+/// Monitors are numbered by their order,
+/// the first one is the main display.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DisplayLayout {
+    pub monitors: Vec<Monitor>,
+}
+
+impl DisplayLayout {
+    pub fn new() -> DisplayLayout {
+        DisplayLayout::default()
+    }
+
+    /// One monitor of `width` and `height` at `(0, 0)`.
+    pub fn single(width: u32, height: u32) -> DisplayLayout {
+        DisplayLayout::new().with_monitor(Monitor::new(0, 0, width, height))
+    }
+
+    /// Add a monitor after the others.
+    pub fn with_monitor(mut self, monitor: Monitor) -> DisplayLayout {
+        self.monitors.push(monitor);
+        self
+    }
+
+    pub fn monitor(&self, index: usize) -> Option<&Monitor> {
+        self.monitors.get(index)
+    }
+
+    /// The main display.
+    pub fn primary(&self) -> Option<&Monitor> {
+        self.monitors.first()
+    }
+
+    /// Index of the monitor a global pixel position is on.
+    pub fn monitor_at(&self, point: (i32, i32)) -> Option<usize> {
+        self.monitors.iter().position(|m| m.contains(point))
+    }
+}
+
+impl fmt::Display for DisplayLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, monitor) in self.monitors.iter().enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{i}: {monitor}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Move mouse to a position relative to the screen.
+/// Created by [`MousePosition::move_to_screen`].
+///
+/// The simulator is asked for the [`DisplayLayout`] when this is simulated,
+/// the position is on its main display unless it's on another monitor.
+/// A layout of a single monitor is assumed if only the [`ScreenSize`] is known.
+/// Nothing is simulated if it doesn't know.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScreenMove<P> {
+    pub to: P,
+    /// Index of the monitor in the layout, the main display if `None`
+    pub monitor: Option<usize>,
+}
+
+impl<P> ScreenMove<P> {
+    pub fn new(to: P) -> ScreenMove<P> {
+        ScreenMove { to, monitor: None }
+    }
+
+    /// Move to the position on a monitor of the [`DisplayLayout`] instead.
+    pub fn on_monitor(mut self, index: usize) -> ScreenMove<P> {
+        self.monitor = Some(index);
+        self
+    }
+}
+
+/// Mouse position on a monitor of the [`DisplayLayout`].
+/// Created by [`MousePosition::on_monitor`].
+///
+/// Moves made from this are [`ScreenMove`]s on the monitor,
+/// converted to global pixels when they are simulated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OnMonitor(pub usize);
+
+impl OnMonitor {
+    /// Move mouse to pixels relative to the top left corner of the monitor.
+    pub fn move_to(self, x: i32, y: i32) -> ScreenMove<(i32, i32)> {
+        ScreenMove::new((x, y)).on_monitor(self.0)
+    }
+
+    /// Move mouse to a position relative to the monitor.
+    pub fn move_to_screen<P: ScreenPoint>(self, to: P) -> ScreenMove<P> {
+        ScreenMove::new(to).on_monitor(self.0)
+    }
+}
+
+impl<P, Smlt> Simulatable<Smlt> for ScreenMove<P>
+where
+    P: ScreenPoint,
+    Smlt: Query<ScreenSize> + Query<Displays> + Simulate<SetTo<MousePosition, (i32, i32)>>,
+{
+    fn run_with(self, simulator: &mut Smlt) {
+        let target = simulator
+            .query(Displays)
+            .or_else(|| {
+                let (width, height) = simulator.query(ScreenSize)?;
+                Some(DisplayLayout::single(width, height))
+            })
+            .and_then(|layout| match self.monitor {
+                None => layout.primary().copied(),
+                Some(index) => layout.monitor(index).copied(),
+            });
+        if let Some(monitor) = target {
+            let (x, y) = monitor.to_global(&self.to);
+            MousePosition.move_to(x, y).run_with(simulator);
+        }
+    }
+}
+
+impl<P> Estimate for ScreenMove<P> {
+    fn estimate(&self) -> Estimation {
+        Estimation::EVENT
+    }
+}
+
+impl<P> fmt::Display for ScreenMove<P>
+where
+    P: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.monitor {
+            None => write!(f, "[move mouse to {} of screen]", self.to),
+            Some(index) => write!(f, "[move mouse to {} of monitor {index}]", self.to),
+        }
+    }
+}
+
+/// Layer that answers [`ScreenSize`] and [`Displays`] with a configured layout,
+/// for simulators that can't or to pretend a different resolution.
+///
+/// The screen size is the size of the first monitor.
+/// Every other question is answered by the inner simulator
+/// and every simulatable is passed on as is.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ScreenSized {
+    pub layout: DisplayLayout,
+}
+
+impl ScreenSized {
+    /// A single monitor of `width` and `height`.
+    pub fn new(width: u32, height: u32) -> ScreenSized {
+        ScreenSized::with_layout(DisplayLayout::single(width, height))
+    }
+
+    pub fn with_layout(layout: DisplayLayout) -> ScreenSized {
+        ScreenSized { layout }
+    }
+}
+
+impl<E, S> Layer<E, S> for ScreenSized
+where
+    S: Simulate<E>,
+{
+    fn handle(&mut self, simulatable: E, inner: &mut S) {
+        inner.simulate(simulatable)
+    }
+}
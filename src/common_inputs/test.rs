@@ -0,0 +1,314 @@
+use std::time::Duration;
+
+use crate::estimate::{Estimate, Estimation};
+use crate::prelude::*;
+use crate::query::{Displays, NoQuery, Query, ScreenSize};
+use crate::{assert_event, simulators::string_event_logger::StringEventLogger as S};
+use crate::{combinator::Sleep, input_event::*, simulator::Simulate};
+
+use super::mouse_path::{Easing, Path};
+use super::screen::{
+    Anchor, DisplayLayout, Monitor, Normalized, Percent, ScreenPoint, ScreenSized,
+};
+
+#[test]
+fn mouse_path_relative() {
+    let mut s = S::new();
+    let x = MousePosition
+        .move_along(Path::linear((10, 10), (20, 30)))
+        .duration_ms(2)
+        .rate(1000)
+        .relative();
+    x.run_with(&mut s);
+    assert_event!(s, 0, MousePosition.move_by(5, 10));
+    assert_event!(s, 1, MousePosition.move_by(5, 10));
+    assert_eq!(s.data.len(), 2);
+}
+
+#[test]
+fn mouse_path_easing_ends() {
+    for easing in [
+        Easing::Linear,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+        Easing::Sine,
+    ] {
+        assert!(easing.apply(0.0).abs() < 1e-9, "{easing} at 0");
+        assert!((easing.apply(1.0) - 1.0).abs() < 1e-9, "{easing} at 1");
+    }
+}
+
+#[test]
+fn mouse_path_circle() {
+    let path = Path::circle((100, 100), 50.0);
+    assert_eq!(path.start(), (150, 100));
+    assert_eq!(path.end(), (150, 100));
+    assert_eq!(super::mouse_path::MouseMove::new(path).rate(0).steps(), 1);
+}
+
+#[test]
+fn mouse_path_human() {
+    let a = Path::human((0, 0), (300, 200), 42);
+    let b = Path::human((0, 0), (300, 200), 42);
+    assert_eq!(a.start(), (0, 0));
+    assert_eq!(a.end(), (300, 200));
+    assert_eq!(a.point_at(0.5), b.point_at(0.5));
+    assert_ne!(
+        a.point_at(0.5),
+        Path::linear((0, 0), (300, 200)).point_at(0.5)
+    );
+}
+
+#[test]
+fn mouse_path_step_duration() {
+    let x = MousePosition
+        .move_along(Path::linear((0, 0), (1, 1)))
+        .duration(Duration::from_secs(1))
+        .rate(10);
+    assert_eq!(x.steps(), 10);
+    assert_eq!(x.step_duration(), Duration::from_millis(100));
+}
+
+#[test]
+fn mouse_path_empty_bezier() {
+    let path = Path::Bezier { points: vec![] };
+    assert_eq!(path.start(), (0, 0));
+    let mut s = S::new();
+    let x = MousePosition.move_along(path.clone()).duration_ms(10);
+    assert_eq!(x.estimate(), Estimation::ZERO);
+    x.run_with(&mut s);
+    MousePosition
+        .move_along(path.clone())
+        .relative()
+        .run_with(&mut s);
+    assert!(s.data.is_empty());
+    MouseButton::Left
+        .drag_along(MousePosition.move_along(path))
+        .run_with(&mut s);
+    assert_event!(s, 0, MouseButton::Left.down());
+    assert_event!(s, 1, MouseButton::Left.up());
+    assert_eq!(s.data.len(), 2);
+}
+
+#[test]
+fn drag() {
+    let mut s = S::new();
+    let x = MouseButton::Left.drag_along(
+        MousePosition
+            .move_along(Path::linear((0, 0), (10, 20)))
+            .duration_ms(2)
+            .rate(1000),
+    );
+    x.run_with(&mut s);
+    assert_event!(s, 0, MousePosition.move_to(0, 0));
+    assert_event!(s, 1, MouseButton::Left.down());
+    assert_event!(s, 2, MousePosition.move_to(5, 10));
+    assert_event!(s, 3, MousePosition.move_to(10, 20));
+    assert_event!(s, 4, MouseButton::Left.up());
+    assert_eq!(s.data.len(), 5);
+}
+
+/// Panics on the nth mouse move
+struct FailingMover {
+    logger: S,
+    moves_left: usize,
+}
+
+impl Simulate<SetTo<MousePosition, (i32, i32)>> for FailingMover {
+    fn simulate(&mut self, simulatable: SetTo<MousePosition, (i32, i32)>) {
+        if self.moves_left == 0 {
+            panic!("mouse is stuck");
+        }
+        self.moves_left -= 1;
+        self.logger.push(&simulatable);
+    }
+}
+
+impl Simulate<ChangeBy<MousePosition, (i32, i32)>> for FailingMover {
+    fn simulate(&mut self, simulatable: ChangeBy<MousePosition, (i32, i32)>) {
+        self.logger.push(&simulatable);
+    }
+}
+
+impl Simulate<SetTo<MouseButton, bool>> for FailingMover {
+    fn simulate(&mut self, simulatable: SetTo<MouseButton, bool>) {
+        self.logger.push(&simulatable);
+    }
+}
+
+impl Simulate<Sleep> for FailingMover {
+    fn simulate(&mut self, simulatable: Sleep) {
+        self.logger.simulate(simulatable);
+    }
+}
+
+#[test]
+fn drag_releases_on_panic() {
+    let mut s = FailingMover {
+        logger: S::new(),
+        moves_left: 1,
+    };
+    let x = MouseButton::Right.drag((0, 0), (100, 100));
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| x.run_with(&mut s)));
+    assert!(result.is_err());
+    let s = s.logger;
+    assert_event!(s, 0, MousePosition.move_to(0, 0));
+    assert_event!(s, 1, MouseButton::Right.down());
+    assert_event!(s, 2, MouseButton::Right.up());
+    assert_eq!(s.data.len(), 3);
+}
+
+#[test]
+fn screen_move() {
+    assert_eq!(Anchor::BottomRight.to_pixels((1, 1)), (0, 0));
+    assert_eq!(Anchor::Bottom.to_pixels((0, 0)), (0, 0));
+    assert_eq!(Percent(50.0, 25.0).to_pixels((101, 101)), (50, 25));
+    assert_eq!(Normalized(-0.5, 2.0).to_pixels((11, 11)), (-5, 20));
+    assert_eq!((3, 4).to_pixels((10, 10)), (3, 4));
+
+    // the size is answered through the tracked state
+    let mut s = S::new().layer(ScreenSized::new(800, 600)).tracked();
+    MousePosition
+        .move_to_screen(Anchor::BottomLeft.offset(5, -5))
+        .run_with(&mut s);
+    assert_eq!(s.layer.mouse_position(), (5, 594));
+    let logger = s.inner.inner;
+    assert_event!(logger, 0, MousePosition.move_to(5, 594));
+
+    // nothing is simulated if the size is not known
+    let mut s = S::new().layer(NoQuery);
+    MousePosition
+        .move_to_screen(Anchor::Center)
+        .run_with(&mut s);
+    assert!(s.inner.data.is_empty());
+
+    assert_eq!(
+        MousePosition
+            .move_to_screen(Anchor::Top.offset(0, 10))
+            .to_string(),
+        "[move mouse to top + (0, 10) of screen]"
+    );
+}
+
+#[test]
+fn screen_move_monitor() {
+    let layout = DisplayLayout::single(1920, 1080)
+        .with_monitor(Monitor::new(-1280, 56, 1280, 1024))
+        .with_monitor(Monitor::new(1920, 0, 1080, 1920));
+    assert_eq!(layout.monitor_at((0, 0)), Some(0));
+    assert_eq!(layout.monitor_at((-1, 56)), Some(1));
+    assert_eq!(layout.monitor_at((-1, 55)), None);
+    assert_eq!(layout.monitor_at((1920 + 1079, 1919)), Some(2));
+    assert_eq!(layout.monitor_at((1920, 1920)), None);
+    assert_eq!(
+        layout.to_string(),
+        "0: 1920x1080 at (0, 0), 1: 1280x1024 at (-1280, 56), 2: 1080x1920 at (1920, 0)"
+    );
+
+    let mut s = S::new().layer(ScreenSized::with_layout(layout)).tracked();
+    MousePosition
+        .move_to_screen(Anchor::TopLeft)
+        .on_monitor(1)
+        .run_with(&mut s);
+    MousePosition
+        .move_to_screen((10, 20))
+        .on_monitor(2)
+        .run_with(&mut s);
+    // out of range
+    MousePosition
+        .move_to_screen(Anchor::Center)
+        .on_monitor(3)
+        .run_with(&mut s);
+    MousePosition
+        .move_to_screen(Anchor::Center)
+        .run_with(&mut s);
+    MousePosition.on_monitor(1).move_to(10, 20).run_with(&mut s);
+    let logger = s.inner.inner;
+    assert_event!(logger, 0, MousePosition.move_to(-1280, 56));
+    assert_event!(logger, 1, MousePosition.move_to(1930, 20));
+    assert_event!(logger, 2, MousePosition.move_to(960, 540));
+    assert_event!(logger, 3, MousePosition.move_to(-1270, 76));
+    assert_eq!(logger.data.len(), 4);
+
+    // only the screen size is known, so it's the only monitor
+    let mut s = ScreenSizeOnly(S::new());
+    MousePosition
+        .move_to_screen(Anchor::BottomRight)
+        .on_monitor(0)
+        .run_with(&mut s);
+    MousePosition
+        .move_to_screen(Anchor::BottomRight)
+        .on_monitor(1)
+        .run_with(&mut s);
+    let logger = s.0;
+    assert_event!(logger, 0, MousePosition.move_to(99, 49));
+    assert_eq!(logger.data.len(), 1);
+
+    assert_eq!(
+        MousePosition
+            .move_to_screen(Percent(50.0, 10.0))
+            .on_monitor(1)
+            .to_string(),
+        "[move mouse to (50%, 10%) of monitor 1]"
+    );
+}
+
+#[test]
+fn screen_move_primary_origin() {
+    // the main display isn't at (0, 0)
+    let layout = DisplayLayout::new()
+        .with_monitor(Monitor::new(1280, 0, 1920, 1080))
+        .with_monitor(Monitor::new(0, 0, 1280, 1024));
+    let mut s = S::new().layer(ScreenSized::with_layout(layout));
+    MousePosition
+        .move_to_screen(Anchor::TopLeft.offset(10, 20))
+        .run_with(&mut s);
+    // without the layout it's the screen size from (0, 0)
+    let mut logger = ScreenSizeOnly(s.inner);
+    MousePosition
+        .move_to_screen(Anchor::TopLeft.offset(10, 20))
+        .run_with(&mut logger);
+    let logger = logger.0;
+    assert_event!(logger, 0, MousePosition.move_to(1290, 20));
+    assert_event!(logger, 1, MousePosition.move_to(10, 20));
+}
+
+#[test]
+fn screen_move_saturates() {
+    let monitor = Monitor::new(i32::MAX - 10, i32::MIN + 10, 100, 100);
+    assert_eq!(
+        Anchor::BottomRight
+            .offset(i32::MAX, i32::MAX)
+            .to_pixels((100, 100)),
+        (i32::MAX, i32::MAX)
+    );
+    assert_eq!(
+        monitor.to_global(&Anchor::Center.offset(0, -100)),
+        (i32::MAX, i32::MIN)
+    );
+}
+
+/// Logger that knows the screen size but not the display layout.
+struct ScreenSizeOnly(S);
+
+impl<E> Simulate<E> for ScreenSizeOnly
+where
+    S: Simulate<E>,
+{
+    fn simulate(&mut self, simulatable: E) {
+        self.0.simulate(simulatable)
+    }
+}
+
+impl Query<ScreenSize> for ScreenSizeOnly {
+    fn query(&self, _: ScreenSize) -> Option<(u32, u32)> {
+        Some((100, 50))
+    }
+}
+
+impl Query<Displays> for ScreenSizeOnly {
+    fn query(&self, _: Displays) -> Option<DisplayLayout> {
+        None
+    }
+}
@@ -0,0 +1,307 @@
+//! Module for cancelling and pausing a simulation from another thread
+//!
+//! [`Control::run_with`] runs a simulatable with a control handle in effect.
+//! [`Sequence`](crate::combinator::Sequence), [`Repeat`](crate::combinator::Repeat),
+//! [`IterSequence`](crate::combinator::IterSequence) and
+//! [`Sleep`](crate::combinator::Sleep) call [`checkpoint`] between their steps,
+//! which waits while paused and stops them once cancelled.
+//! Sleeping simulators wake up early when cancelled.
+//!
+//! The control is also a [`Layer`] in front of the simulator,
+//! every other simulatable waits there while paused
+//! and nothing reaches the simulator once cancelled.
+//! Every input still held when cancelled is released.
+//!
+//! ```
+//! # use kemuler::simulators::string_event_logger::StringEventLogger as Simulator;
+//! use std::{thread, time::Duration};
+//! use kemuler::prelude::*;
+//! use kemuler::control::{Cancelled, Control};
+//!
+//! let control = Control::new();
+//! let handle = control.clone();
+//! thread::spawn(move || {
+//!     thread::sleep(Duration::from_millis(50));
+//!     handle.cancel();
+//! });
+//!
+//! let mut s = Simulator::new();
+//! // would take 100 seconds
+//! let result = control.run_with(
+//!     MouseButton::Left.click().sleep_ms(10).repeat(10_000).during(Key::Shift.down()),
+//!     &mut s,
+//! );
+//!
+//! assert_eq!(result, Err(Cancelled));
+//! assert!(s.data.len() < 100);
+//! assert_eq!(s.data.last(), Some(&format!("{:?}", Key::Shift.up())));
+//! ```
+
+use std::{
+    cell::RefCell,
+    error::Error,
+    fmt,
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    common_inputs::{Char, Key, MouseButton},
+    input_event::SetTo,
+    simulatable::Simulatable,
+    simulator::{Layer, Layered, Simulate, SimulatorExt},
+    simulators::virtual_state::Tracked,
+};
+
+#[cfg(test)]
+mod test;
+
+/// A simulation has been cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "simulation cancelled")
+    }
+}
+
+impl Error for Cancelled {}
+
+#[derive(Debug, Default)]
+struct Flags {
+    cancelled: bool,
+    paused: bool,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    flags: Mutex<Flags>,
+    changed: Condvar,
+}
+
+/// Longest a spin sleep goes without checking its control.
+#[cfg(feature = "spin_sleep")]
+pub const SPIN_SLICE: Duration = Duration::from_millis(10);
+
+/// Handle to cancel or pause a simulation.
+///
+/// Cloning this shares the same handle, so it can be kept in another thread.
+/// A cancelled handle stays cancelled, create a new one for the next simulation.
+#[derive(Debug, Clone, Default)]
+pub struct Control(Arc<Inner>);
+
+thread_local! {
+    static CURRENT: RefCell<Option<Control>> = const { RefCell::new(None) };
+}
+
+impl Control {
+    pub fn new() -> Control {
+        Control::default()
+    }
+
+    /// Control of the simulation running in this thread.
+    pub fn current() -> Option<Control> {
+        CURRENT.with(|current| current.borrow().clone())
+    }
+
+    /// Stop at the next checkpoint, also wakes up sleeps and pauses.
+    pub fn cancel(&self) {
+        self.update(|flags| flags.cancelled = true)
+    }
+
+    /// Wait at the next checkpoint until resumed.
+    pub fn pause(&self) {
+        self.update(|flags| flags.paused = true)
+    }
+
+    pub fn resume(&self) {
+        self.update(|flags| flags.paused = false)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.lock().cancelled
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.lock().paused
+    }
+
+    /// Wait while paused, `Err` if cancelled.
+    pub fn checkpoint(&self) -> Result<(), Cancelled> {
+        let mut flags = self.lock();
+        while flags.paused && !flags.cancelled {
+            flags = self.wait(flags);
+        }
+        if flags.cancelled {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Sleep for `duration` not counting the time paused,
+    /// wakes up early with `Err` if cancelled.
+    pub fn sleep(&self, duration: Duration) -> Result<(), Cancelled> {
+        let mut remaining = duration;
+        let mut flags = self.lock();
+        loop {
+            if flags.cancelled {
+                return Err(Cancelled);
+            }
+            if flags.paused {
+                flags = self.wait(flags);
+                continue;
+            }
+            if remaining.is_zero() {
+                return Ok(());
+            }
+            let start = Instant::now();
+            flags = self
+                .0
+                .changed
+                .wait_timeout(flags, remaining)
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .0;
+            remaining = remaining.saturating_sub(start.elapsed());
+        }
+    }
+
+    /// Spin sleep for `duration` not counting the time paused,
+    /// wakes up early with `Err` if cancelled.
+    ///
+    /// Spins in slices of [`SPIN_SLICE`] and checks this control between them.
+    #[cfg(feature = "spin_sleep")]
+    pub fn spin_sleep(&self, duration: Duration) -> Result<(), Cancelled> {
+        let mut remaining = duration;
+        loop {
+            self.checkpoint()?;
+            if remaining.is_zero() {
+                return Ok(());
+            }
+            let slice = remaining.min(SPIN_SLICE);
+            spin_sleep::sleep(slice);
+            remaining -= slice;
+        }
+    }
+
+    /// Run a simulatable with this control in effect in this thread.
+    ///
+    /// The simulator is wrapped in this control, see [`Controlled`],
+    /// every input still held is released when cancelled.
+    pub fn run_with<'a, S, Smlt>(
+        &self,
+        simulatable: S,
+        simulator: &'a mut Smlt,
+    ) -> Result<(), Cancelled>
+    where
+        S: Simulatable<Controlled<&'a mut Smlt>>,
+        Smlt: Simulate<SetTo<Key, bool>>
+            + Simulate<SetTo<MouseButton, bool>>
+            + Simulate<SetTo<Char, bool>>,
+    {
+        let mut simulator = simulator.tracked().layer(self.clone());
+        {
+            let _guard = self.enter();
+            simulatable.run_with(&mut simulator);
+        }
+        if self.is_cancelled() {
+            simulator.inner.release_all();
+            return Err(Cancelled);
+        }
+        Ok(())
+    }
+
+    /// Put this control in effect in this thread until the guard is dropped.
+    ///
+    /// Held inputs are not released, see [`Control::run_with`] for that.
+    pub fn enter(&self) -> ControlGuard {
+        let previous = CURRENT.with(|current| current.replace(Some(self.clone())));
+        ControlGuard { previous }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Flags> {
+        self.0
+            .flags
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn wait<'a>(&self, flags: MutexGuard<'a, Flags>) -> MutexGuard<'a, Flags> {
+        self.0
+            .changed
+            .wait(flags)
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Flags)) {
+        f(&mut self.lock());
+        self.0.changed.notify_all();
+    }
+}
+
+/// Pass simulatables on after [`Control::checkpoint`], drop them once cancelled.
+impl<E, S> Layer<E, S> for Control
+where
+    S: Simulate<E>,
+{
+    fn handle(&mut self, simulatable: E, inner: &mut S) {
+        if self.checkpoint().is_ok() {
+            inner.simulate(simulatable)
+        }
+    }
+}
+
+/// Simulator that [`Control::run_with`] runs a simulatable with,
+/// inputs are tracked to be released and the control drops them once cancelled.
+pub type Controlled<S> = Layered<Control, Tracked<S>>;
+
+/// Puts the previous control back in effect when dropped.
+/// Created by [`Control::enter`].
+#[derive(Debug)]
+#[must_use = "the control is only in effect until the guard is dropped"]
+pub struct ControlGuard {
+    previous: Option<Control>,
+}
+
+impl Drop for ControlGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+/// Check the control in effect in this thread, see [`Control::checkpoint`].
+/// Always `Ok` without one.
+pub fn checkpoint() -> Result<(), Cancelled> {
+    match Control::current() {
+        Some(control) => control.checkpoint(),
+        None => Ok(()),
+    }
+}
+
+/// Sleep that wakes up early when the control in effect in this thread is cancelled.
+///
+/// Simulators that sleep for real should sleep with this.
+pub fn sleep(duration: Duration) {
+    match Control::current() {
+        Some(control) => {
+            let _ = control.sleep(duration);
+        }
+        None => thread::sleep(duration),
+    }
+}
+
+/// Spin sleep that wakes up early when the control in effect in this thread is cancelled.
+///
+/// Simulators that spin sleep for real should sleep with this.
+#[cfg(feature = "spin_sleep")]
+pub fn spin_sleep(duration: Duration) {
+    match Control::current() {
+        Some(control) => {
+            let _ = control.spin_sleep(duration);
+        }
+        None => spin_sleep::sleep(duration),
+    }
+}
@@ -0,0 +1,188 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::assert_event;
+use crate::combinator::Sleep;
+use crate::prelude::*;
+use crate::simulator::Simulate;
+use crate::simulators::string_event_logger::StringEventLogger as S;
+use crate::timeline::Timeline;
+
+use super::{checkpoint, Cancelled, Control};
+
+/// Do `f` with the control after `ms` in another thread.
+fn after(ms: u64, control: &Control, f: fn(&Control)) {
+    let control = control.clone();
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(ms));
+        f(&control);
+    });
+}
+
+/// Logger that cancels the control once it has logged `at` events.
+struct CancelAt {
+    logger: S,
+    control: Control,
+    at: usize,
+}
+
+impl<E> Simulate<E> for CancelAt
+where
+    S: Simulate<E>,
+{
+    fn simulate(&mut self, simulatable: E) {
+        self.logger.simulate(simulatable);
+        if self.logger.data.len() == self.at {
+            self.control.cancel();
+        }
+    }
+}
+
+#[test]
+fn control_runs_to_completion() {
+    let mut s = S::new();
+    let control = Control::new();
+    let result = control.run_with(
+        (Key::Shift.down(), MouseButton::Left.click().repeat(2)).seq(),
+        &mut s,
+    );
+    assert_eq!(result, Ok(()));
+    // held inputs are only released when cancelled
+    assert_eq!(s.data.len(), 5);
+    assert_event!(s, 4, MouseButton::Left.up());
+}
+
+#[test]
+fn control_cancel_releases_held() {
+    let mut s = S::new();
+    let control = Control::new();
+    after(30, &control, Control::cancel);
+    let result = control.run_with(
+        (
+            Key::Shift.down(),
+            Char('a').down(),
+            MouseButton::Left.click().sleep_ms(5).repeat(10_000),
+        )
+            .seq(),
+        &mut s,
+    );
+    assert_eq!(result, Err(Cancelled));
+    assert!(s.data.len() < 100);
+    let n = s.data.len();
+    assert_event!(s, n - 1, Key::Shift.up());
+    assert_event!(s, n - 2, Char('a').up());
+    assert_event!(s, n - 3, MouseButton::Left.up());
+}
+
+#[test]
+fn control_cancel_wakes_sleep() {
+    let mut s = S::new();
+    let control = Control::new();
+    after(20, &control, Control::cancel);
+    let start = Instant::now();
+    let result = control.run_with(
+        [
+            Sleep(Duration::from_secs(10)),
+            Sleep(Duration::from_secs(10)),
+        ]
+        .iter_seq(),
+        &mut s,
+    );
+    assert_eq!(result, Err(Cancelled));
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn control_pause_resume() {
+    let mut s = S::new();
+    let control = Control::new();
+    control.pause();
+    after(50, &control, Control::resume);
+    let start = Instant::now();
+    let result = control.run_with(Key::Tab.click().repeat(2), &mut s);
+    assert_eq!(result, Ok(()));
+    assert!(start.elapsed() >= Duration::from_millis(50));
+    assert_eq!(s.data.len(), 4);
+}
+
+#[test]
+fn control_cancel_while_paused() {
+    let mut s = S::new();
+    let control = Control::new();
+    control.pause();
+    after(20, &control, Control::cancel);
+    let result = control.run_with(Key::Tab.click(), &mut s);
+    assert_eq!(result, Err(Cancelled));
+    assert!(s.data.is_empty());
+}
+
+#[test]
+fn control_enter_restores_previous() {
+    assert_eq!(checkpoint(), Ok(()));
+    let outer = Control::new();
+    let cancelled = Control::new();
+    cancelled.cancel();
+    {
+        let _outer = outer.enter();
+        {
+            let _inner = cancelled.enter();
+            assert_eq!(checkpoint(), Err(Cancelled));
+        }
+        assert_eq!(checkpoint(), Ok(()));
+    }
+    assert!(Control::current().is_none());
+}
+
+#[test]
+fn control_cancel_drops_timeline_events() {
+    let control = Control::new();
+    let mut s = CancelAt {
+        logger: S::new(),
+        control: control.clone(),
+        at: 4,
+    };
+    let timeline =
+        Timeline::compile((Key::Shift.down(), Char('a').click().sleep_ms(1).repeat(50)).seq());
+    assert_eq!(control.run_with(timeline, &mut s), Err(Cancelled));
+    // nothing after the cancel but the releases
+    let s = s.logger;
+    assert_event!(s, 3, Char('a').down());
+    assert_event!(s, 4, Char('a').up());
+    assert_event!(s, 5, Key::Shift.up());
+    assert_eq!(s.data.len(), 6);
+}
+
+#[test]
+fn control_cancel_drops_mouse_move_events() {
+    let control = Control::new();
+    let mut s = CancelAt {
+        logger: S::new(),
+        control: control.clone(),
+        at: 3,
+    };
+    let result = control.run_with(MouseButton::Left.drag((0, 0), (100, 0)), &mut s);
+    assert_eq!(result, Err(Cancelled));
+    let s = s.logger;
+    assert_event!(s, 1, MouseButton::Left.down());
+    // released once by the control, the drag's own release is dropped
+    assert_event!(s, 3, MouseButton::Left.up());
+    assert_eq!(s.data.len(), 4);
+}
+
+#[cfg(feature = "spin_sleep")]
+#[test]
+fn control_cancel_wakes_spin_sleep() {
+    use crate::combinator::SpinSleep;
+
+    let mut s = S::new();
+    let control = Control::new();
+    after(20, &control, Control::cancel);
+    let start = Instant::now();
+    let result = control.run_with(
+        (SpinSleep(Duration::from_secs(10)), Key::Tab.click()).seq(),
+        &mut s,
+    );
+    assert_eq!(result, Err(Cancelled));
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert!(s.data.is_empty());
+}
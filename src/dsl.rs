@@ -0,0 +1,411 @@
+//! Text macros mixing plain text and special keys.
+//!
+//! Two flavours are supported:
+//! - [`Script::parse_sendkeys`]: SendKeys/AutoHotkey style,
+//!   e.g. `"Hello{ENTER}{TAB 3}^a+{HOME}"`
+//! - [`Script::parse_vim`]: vim key notation style,
+//!   e.g. `"<C-a><Esc>:wq<CR>"`
+//!
+//! ```
+//! # use kemuler::simulators::string_event_logger::StringEventLogger as Simulator;
+//! # use kemuler::assert_event;
+//! use kemuler::prelude::*;
+//! use kemuler::dsl::Script;
+//!
+//! let mut s = Simulator::new();
+//!
+//! Script::parse_sendkeys("Hi{ENTER}^a").unwrap().run_with(&mut s);
+//!
+//! assert_event!(s, 0, Char('H').down());
+//! assert_event!(s, 1, Char('H').up());
+//! assert_event!(s, 2, Char('i').down());
+//! assert_event!(s, 3, Char('i').up());
+//! assert_event!(s, 4, Key::Enter.down());
+//! assert_event!(s, 5, Key::Enter.up());
+//! assert_event!(s, 6, Key::Control.down());
+//! assert_event!(s, 7, Char('a').down());
+//! assert_event!(s, 8, Char('a').up());
+//! assert_event!(s, 9, Key::Control.up());
+//! assert_eq!(s.data.len(), 10);
+//! ```
+//!
+//! # SendKeys syntax
+//! - Any character is typed as is, except the special ones below.
+//! - `{NAME}` clicks a named key, see [`key_from_name`](crate::chord::key_from_name).
+//!   `BS`, `BKSP` and `LWIN` are also accepted.
+//! - `{NAME N}` clicks a named key, or a character, `N` times.
+//!   `N` is at most [`MAX_REPEAT_COUNT`].
+//! - `{+}`, `{^}`, `{%}`, `{#}`, `{~}`, `{{}`, `{}}`, `{(}` and `{)}` type the character itself.
+//! - `~` clicks Enter.
+//! - `+`, `^`, `%` and `#` hold Shift, Control, Alt and Meta
+//!   during the next key or the next `( group )`.
+//!
+//! # Vim syntax
+//! - Any character is typed as is, except `<`.
+//! - `<NAME>` clicks a named key, `<lt>` types `<`, `<CR>` is Enter.
+//! - `<C-…>`, `<S-…>`, `<A-…>` (or `<M-…>`) and `<D-…>` hold Control, Shift, Alt and Meta
+//!   during the key, e.g. `<C-S-Tab>`.
+//! - `<` that doesn't start a key as above is typed as is,
+//!   e.g. `"a < b"` and `"<Foo>"`.
+
+use std::{error::Error, fmt, iter::Peekable, str::CharIndices};
+
+use crate::{
+    chord::{self, ChordKey},
+    common_inputs::{ButtonLike, Char, Key},
+    estimate::{Estimate, Estimation},
+    input_event::SetTo,
+    simulatable::Simulatable,
+    simulator::Simulate,
+};
+
+#[cfg(test)]
+mod test;
+
+/// One step of a [`Script`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Step {
+    /// Press and release a key or a character.
+    Click(ChordKey),
+    /// Hold keys down in order during steps, then release them in reverse order.
+    Hold { keys: Vec<Key>, steps: Vec<Step> },
+}
+
+impl<Smlt> Simulatable<Smlt> for Step
+where
+    Smlt: Simulate<SetTo<Key, bool>> + Simulate<SetTo<Char, bool>>,
+{
+    fn run_with(self, simulator: &mut Smlt) {
+        match self {
+            Step::Click(ChordKey::Key(key)) => key.click().run_with(simulator),
+            Step::Click(ChordKey::Char(c)) => c.click().run_with(simulator),
+            Step::Hold { keys, steps } => {
+                for key in &keys {
+                    key.down().run_with(simulator);
+                }
+                for step in steps {
+                    step.run_with(simulator);
+                }
+                for key in keys.iter().rev() {
+                    key.up().run_with(simulator);
+                }
+            }
+        }
+    }
+}
+
+impl Estimate for Step {
+    fn estimate(&self) -> Estimation {
+        match self {
+            Step::Click(_) => Estimation::events(2),
+            Step::Hold { keys, steps } => {
+                Estimation::events(keys.len() * 2) + steps.iter().map(Step::estimate).sum()
+            }
+        }
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::Click(key) => write!(f, "[click {key}]"),
+            Step::Hold { keys, steps } => {
+                write!(f, "[holding (")?;
+                for (i, key) in keys.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, "+")?;
+                    }
+                    write!(f, "{}", chord::key_name(*key))?;
+                }
+                write!(f, "), do (")?;
+                for step in steps {
+                    write!(f, "{step}")?;
+                }
+                write!(f, ")]")
+            }
+        }
+    }
+}
+
+/// Sequence of steps parsed from a text macro.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Script(pub Vec<Step>);
+
+impl Script {
+    /// Parse SendKeys/AutoHotkey style text, see the [module documentation](self).
+    pub fn parse_sendkeys(source: &str) -> Result<Script, ParseScriptError> {
+        let mut parser = Parser::new(source);
+        let steps = parser.sendkeys_sequence(None)?;
+        Ok(Script(steps))
+    }
+
+    /// Parse vim key notation style text, see the [module documentation](self).
+    ///
+    /// Text that isn't a key notation is typed as is, so this doesn't fail for now.
+    pub fn parse_vim(source: &str) -> Result<Script, ParseScriptError> {
+        let mut parser = Parser::new(source);
+        Ok(Script(parser.vim_sequence()))
+    }
+}
+
+impl<Smlt> Simulatable<Smlt> for Script
+where
+    Smlt: Simulate<SetTo<Key, bool>> + Simulate<SetTo<Char, bool>>,
+{
+    fn run_with(self, simulator: &mut Smlt) {
+        for step in self.0 {
+            step.run_with(simulator);
+        }
+    }
+}
+
+impl Estimate for Script {
+    fn estimate(&self) -> Estimation {
+        self.0.iter().map(Step::estimate).sum()
+    }
+}
+
+impl fmt::Display for Script {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for step in &self.0 {
+            write!(f, "{step}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Highest `N` accepted in `{NAME N}`.
+pub const MAX_REPEAT_COUNT: usize = 1000;
+
+/// Error from parsing a [`Script`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseScriptError {
+    /// Byte offset in the source where the error is found
+    pub position: usize,
+    pub kind: ParseScriptErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseScriptErrorKind {
+    /// `{` without `}`
+    UnclosedBrace,
+    /// `(` without `)`
+    UnclosedGroup,
+    /// `)` without `(`
+    UnopenedGroup,
+    /// Modifier at the end with nothing to hold it during
+    DanglingModifier,
+    /// Not a key name
+    UnknownKey(String),
+    /// Repeat count is not a number
+    InvalidCount(String),
+    /// Repeat count is above [`MAX_REPEAT_COUNT`]
+    CountTooLarge(usize),
+}
+
+impl fmt::Display for ParseScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ParseScriptErrorKind::UnclosedBrace => write!(f, "`{{` is never closed"),
+            ParseScriptErrorKind::UnclosedGroup => write!(f, "`(` is never closed"),
+            ParseScriptErrorKind::UnopenedGroup => write!(f, "`)` is never opened"),
+            ParseScriptErrorKind::DanglingModifier => {
+                write!(f, "modifier is not followed by a key")
+            }
+            ParseScriptErrorKind::UnknownKey(name) => write!(f, "unknown key name `{name}`"),
+            ParseScriptErrorKind::InvalidCount(count) => {
+                write!(f, "`{count}` is not a valid repeat count")
+            }
+            ParseScriptErrorKind::CountTooLarge(count) => {
+                write!(f, "repeat count {count} is above {MAX_REPEAT_COUNT}")
+            }
+        }?;
+        write!(f, " at position {}", self.position)
+    }
+}
+
+impl Error for ParseScriptError {}
+
+fn error(position: usize, kind: ParseScriptErrorKind) -> ParseScriptError {
+    ParseScriptError { position, kind }
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> Parser<'a> {
+        Parser {
+            source,
+            chars: source.char_indices().peekable(),
+        }
+    }
+
+    /// Parse until the end, or until `)` if in a group opened at `group`.
+    fn sendkeys_sequence(&mut self, group: Option<usize>) -> Result<Vec<Step>, ParseScriptError> {
+        let mut steps = vec![];
+        loop {
+            match self.chars.peek() {
+                None => match group {
+                    Some(position) => {
+                        return Err(error(position, ParseScriptErrorKind::UnclosedGroup))
+                    }
+                    None => return Ok(steps),
+                },
+                Some(&(_, ')')) if group.is_some() => {
+                    self.chars.next();
+                    return Ok(steps);
+                }
+                Some(_) => steps.extend(self.sendkeys_unit()?),
+            }
+        }
+    }
+
+    /// Parse a single key, a braced key or a group, with its modifiers.
+    fn sendkeys_unit(&mut self) -> Result<Vec<Step>, ParseScriptError> {
+        let mut keys = vec![];
+        let mut last = 0;
+        while let Some(&(position, c)) = self.chars.peek() {
+            let key = match c {
+                '+' => Key::Shift,
+                '^' => Key::Control,
+                '%' => Key::Alt,
+                '#' => Key::Meta,
+                _ => break,
+            };
+            self.chars.next();
+            keys.push(key);
+            last = position;
+        }
+        let Some((position, c)) = self.chars.next() else {
+            return Err(error(last, ParseScriptErrorKind::DanglingModifier));
+        };
+        let steps = match c {
+            '{' => self.sendkeys_braced(position)?,
+            '(' => self.sendkeys_sequence(Some(position))?,
+            ')' if !keys.is_empty() => {
+                return Err(error(last, ParseScriptErrorKind::DanglingModifier))
+            }
+            ')' => return Err(error(position, ParseScriptErrorKind::UnopenedGroup)),
+            '~' => vec![Step::Click(ChordKey::Key(Key::Enter))],
+            c => vec![Step::Click(ChordKey::Char(Char(c)))],
+        };
+        if keys.is_empty() {
+            Ok(steps)
+        } else {
+            Ok(vec![Step::Hold { keys, steps }])
+        }
+    }
+
+    /// Parse after `{` which is at `open`.
+    fn sendkeys_braced(&mut self, open: usize) -> Result<Vec<Step>, ParseScriptError> {
+        let start = open + 1;
+        // `{}}` is the only place `}` can be inside braces
+        let content_end = if self.source[start..].starts_with("}}") {
+            start + 1
+        } else {
+            match self.source[start..].find('}') {
+                Some(i) => start + i,
+                None => return Err(error(open, ParseScriptErrorKind::UnclosedBrace)),
+            }
+        };
+        while self.chars.next_if(|&(i, _)| i <= content_end).is_some() {}
+
+        let content = &self.source[start..content_end];
+        let (name, count) = match content.rsplit_once(' ') {
+            Some((name, count)) if !name.is_empty() => {
+                let position = start + name.len() + 1;
+                let count = count.parse::<usize>().map_err(|_| {
+                    error(
+                        position,
+                        ParseScriptErrorKind::InvalidCount(count.to_owned()),
+                    )
+                })?;
+                if count > MAX_REPEAT_COUNT {
+                    return Err(error(position, ParseScriptErrorKind::CountTooLarge(count)));
+                }
+                (name, count)
+            }
+            _ => (content, 1),
+        };
+        let mut chars = name.chars();
+        let key = match (chars.next(), chars.next()) {
+            (Some(c), None) => ChordKey::Char(Char(c)),
+            _ => match &name.to_ascii_lowercase()[..] {
+                "bs" | "bksp" => ChordKey::Key(Key::Backspace),
+                "lwin" | "rwin" => ChordKey::Key(Key::Meta),
+                _ => chord::key_from_name(name)
+                    .map(ChordKey::Key)
+                    .ok_or_else(|| {
+                        error(start, ParseScriptErrorKind::UnknownKey(name.to_owned()))
+                    })?,
+            },
+        };
+        Ok(vec![Step::Click(key); count])
+    }
+
+    fn vim_sequence(&mut self) -> Vec<Step> {
+        let mut steps = vec![];
+        while let Some((position, c)) = self.chars.next() {
+            if c != '<' {
+                steps.push(Step::Click(ChordKey::Char(Char(c))));
+                continue;
+            }
+            let start = position + 1;
+            let rest = &self.source[start..];
+            let step = rest
+                .find(|c: char| c == '<' || c == '>' || c.is_whitespace())
+                .filter(|&i| i > 0 && rest[i..].starts_with('>'))
+                .and_then(|i| vim_key(&rest[..i]).map(|step| (i, step)));
+            match step {
+                Some((len, step)) => {
+                    while self.chars.next_if(|&(i, _)| i <= start + len).is_some() {}
+                    steps.push(step);
+                }
+                None => steps.push(Step::Click(ChordKey::Char(Char('<')))),
+            }
+        }
+        steps
+    }
+}
+
+/// Parse the content of `<…>`, `None` if it's not a key.
+fn vim_key(content: &str) -> Option<Step> {
+    let mut keys = vec![];
+    let mut name = content;
+    // "<C-->" is control and minus
+    while name.len() > 2 && name.as_bytes()[1] == b'-' {
+        let key = match name.as_bytes()[0].to_ascii_lowercase() {
+            b'c' => Key::Control,
+            b's' => Key::Shift,
+            b'a' | b'm' => Key::Alt,
+            b'd' => Key::Meta,
+            _ => break,
+        };
+        keys.push(key);
+        name = &name[2..];
+    }
+    let mut chars = name.chars();
+    let key = match (chars.next(), chars.next()) {
+        (Some(c), None) => ChordKey::Char(Char(c)),
+        _ => match &name.to_ascii_lowercase()[..] {
+            "lt" => ChordKey::Char(Char('<')),
+            "bar" => ChordKey::Char(Char('|')),
+            "bslash" => ChordKey::Char(Char('\\')),
+            "cr" | "nl" => ChordKey::Key(Key::Enter),
+            "bs" => ChordKey::Key(Key::Backspace),
+            _ => chord::key_from_name(name).map(ChordKey::Key)?,
+        },
+    };
+    let step = Step::Click(key);
+    if keys.is_empty() {
+        Some(step)
+    } else {
+        Some(Step::Hold {
+            keys,
+            steps: vec![step],
+        })
+    }
+}
@@ -0,0 +1,142 @@
+use crate::chord::ChordKey;
+use crate::prelude::*;
+use crate::{assert_event, simulators::string_event_logger::StringEventLogger as S};
+
+use super::{ParseScriptError, ParseScriptErrorKind, Script, Step};
+
+fn click(key: impl Into<ChordKey>) -> Step {
+    Step::Click(key.into())
+}
+
+fn hold(keys: impl IntoIterator<Item = Key>, steps: impl IntoIterator<Item = Step>) -> Step {
+    Step::Hold {
+        keys: keys.into_iter().collect(),
+        steps: steps.into_iter().collect(),
+    }
+}
+
+#[test]
+fn dsl_sendkeys() {
+    assert_eq!(
+        Script::parse_sendkeys("Hi{ENTER}{TAB 3}^a+{HOME}~"),
+        Ok(Script(vec![
+            click(Char('H')),
+            click(Char('i')),
+            click(Key::Enter),
+            click(Key::Tab),
+            click(Key::Tab),
+            click(Key::Tab),
+            hold([Key::Control], [click(Char('a'))]),
+            hold([Key::Shift], [click(Key::Home)]),
+            click(Key::Enter),
+        ]))
+    );
+}
+
+#[test]
+fn dsl_sendkeys_group_and_literals() {
+    assert_eq!(
+        Script::parse_sendkeys("^+(ab){+}{}}{x 2}"),
+        Ok(Script(vec![
+            hold(
+                [Key::Control, Key::Shift],
+                [click(Char('a')), click(Char('b'))]
+            ),
+            click(Char('+')),
+            click(Char('}')),
+            click(Char('x')),
+            click(Char('x')),
+        ]))
+    );
+}
+
+#[test]
+fn dsl_sendkeys_error() {
+    let err = |position, kind| Err(ParseScriptError { position, kind });
+    assert_eq!(
+        Script::parse_sendkeys("ab{ENTER"),
+        err(2, ParseScriptErrorKind::UnclosedBrace)
+    );
+    assert_eq!(
+        Script::parse_sendkeys("{FOO}"),
+        err(1, ParseScriptErrorKind::UnknownKey("FOO".to_owned()))
+    );
+    assert_eq!(
+        Script::parse_sendkeys("{TAB x}"),
+        err(5, ParseScriptErrorKind::InvalidCount("x".to_owned()))
+    );
+    assert!(Script::parse_sendkeys("{TAB 1000}").is_ok());
+    assert_eq!(
+        Script::parse_sendkeys("{TAB 1001}"),
+        err(5, ParseScriptErrorKind::CountTooLarge(1001))
+    );
+    assert_eq!(
+        Script::parse_sendkeys("{a 99999999999999999999}"),
+        err(
+            3,
+            ParseScriptErrorKind::InvalidCount("99999999999999999999".to_owned())
+        )
+    );
+    assert_eq!(
+        Script::parse_sendkeys("a^"),
+        err(1, ParseScriptErrorKind::DanglingModifier)
+    );
+    assert_eq!(
+        Script::parse_sendkeys("^(a"),
+        err(1, ParseScriptErrorKind::UnclosedGroup)
+    );
+    assert_eq!(
+        Script::parse_sendkeys("a)"),
+        err(1, ParseScriptErrorKind::UnopenedGroup)
+    );
+}
+
+#[test]
+fn dsl_vim() {
+    assert_eq!(
+        Script::parse_vim("<C-a><Esc>:wq<CR><lt>a<C-S-Tab><b"),
+        Ok(Script(vec![
+            hold([Key::Control], [click(Char('a'))]),
+            click(Key::Escape),
+            click(Char(':')),
+            click(Char('w')),
+            click(Char('q')),
+            click(Key::Enter),
+            click(Char('<')),
+            click(Char('a')),
+            hold([Key::Control, Key::Shift], [click(Key::Tab)]),
+            click(Char('<')),
+            click(Char('b')),
+        ]))
+    );
+}
+
+#[test]
+fn dsl_vim_literal_lt() {
+    let typed = |text: &str| text.chars().map(|c| click(Char(c))).collect::<Vec<_>>();
+    // not a key, typed as is
+    assert_eq!(Script::parse_vim("x<C-Foo>"), Ok(Script(typed("x<C-Foo>"))));
+    let mut steps = typed("a < b");
+    steps.push(click(Key::Enter));
+    assert_eq!(Script::parse_vim("a < b<CR>"), Ok(Script(steps)));
+    assert_eq!(
+        Script::parse_vim("<<C-a>"),
+        Ok(Script(vec![
+            click(Char('<')),
+            hold([Key::Control], [click(Char('a'))]),
+        ]))
+    );
+}
+
+#[test]
+fn dsl_run_hold() {
+    let mut s = S::new();
+    Script::parse_vim("<A-S-x>").unwrap().run_with(&mut s);
+    assert_event!(s, 0, Key::Alt.down());
+    assert_event!(s, 1, Key::Shift.down());
+    assert_event!(s, 2, Char('x').down());
+    assert_event!(s, 3, Char('x').up());
+    assert_event!(s, 4, Key::Shift.up());
+    assert_event!(s, 5, Key::Alt.up());
+    assert_eq!(s.data.len(), 6);
+}
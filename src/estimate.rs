@@ -0,0 +1,111 @@
+//! Module for estimating a simulatable without simulating it
+
+use std::{
+    fmt, iter,
+    ops::{Add, AddAssign},
+    time::Duration,
+};
+
+/// How long a simulatable takes and how many events it passes to a simulator.
+///
+/// Sleeps count toward `duration` but not toward `events`.
+/// The time spent by the simulator itself is not counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Estimation {
+    pub duration: Duration,
+    pub events: usize,
+}
+
+impl Estimation {
+    /// Estimation of nothing.
+    pub const ZERO: Estimation = Estimation {
+        duration: Duration::ZERO,
+        events: 0,
+    };
+
+    /// Estimation of a single event.
+    pub const EVENT: Estimation = Estimation {
+        duration: Duration::ZERO,
+        events: 1,
+    };
+
+    pub fn events(events: usize) -> Estimation {
+        Estimation {
+            duration: Duration::ZERO,
+            events,
+        }
+    }
+
+    pub fn sleep(duration: Duration) -> Estimation {
+        Estimation {
+            duration,
+            events: 0,
+        }
+    }
+
+    /// Estimation of doing this for amount of times, saturating on overflow.
+    pub fn repeat(self, times: usize) -> Estimation {
+        Estimation {
+            duration: self
+                .duration
+                .saturating_mul(u32::try_from(times).unwrap_or(u32::MAX)),
+            events: self.events.saturating_mul(times),
+        }
+    }
+}
+
+impl Add for Estimation {
+    type Output = Estimation;
+
+    fn add(self, rhs: Estimation) -> Estimation {
+        Estimation {
+            duration: self.duration.saturating_add(rhs.duration),
+            events: self.events.saturating_add(rhs.events),
+        }
+    }
+}
+
+impl AddAssign for Estimation {
+    fn add_assign(&mut self, rhs: Estimation) {
+        *self = *self + rhs;
+    }
+}
+
+impl iter::Sum for Estimation {
+    fn sum<I: Iterator<Item = Estimation>>(iter: I) -> Estimation {
+        iter.fold(Estimation::ZERO, Add::add)
+    }
+}
+
+impl fmt::Display for Estimation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} events in {:?}", self.events, self.duration)
+    }
+}
+
+/// Estimate is a simulatable that knows what it will do before it's simulated.
+/// In this crate, it is implemented on combinators and input event.
+///
+/// ```
+/// use std::time::Duration;
+/// use kemuler::prelude::*;
+/// use kemuler::estimate::{Estimate, Estimation};
+///
+/// let x = (
+///     MouseButton::Left.click(),
+///     Key::Space.click().sleep_ms(10),
+/// )
+///     .seq()
+///     .repeat(3);
+///
+/// assert_eq!(
+///     x.estimate(),
+///     Estimation {
+///         duration: Duration::from_millis(30),
+///         events: 12,
+///     }
+/// );
+/// ```
+pub trait Estimate {
+    fn estimate(&self) -> Estimation;
+}
@@ -0,0 +1,300 @@
+//! Module for starting and stopping a simulatable with hotkeys
+//!
+//! A [`Runner`] listens to an [`InputSource`], usually a keyboard captured with
+//! [`EvdevRecorder`], and runs a simulatable in a background thread
+//! when a bound [`Chord`] is pressed.
+//!
+//! ```no_run
+//! # use kemuler::simulators::string_event_logger::StringEventLogger as Simulator;
+//! use kemuler::prelude::*;
+//! use kemuler::capture::evdev::EvdevRecorder;
+//! use kemuler::hotkey::{Action, Runner};
+//!
+//! let keyboard = EvdevRecorder::open("/dev/input/event3").unwrap();
+//!
+//! // an auto clicker, F6 to start or stop and Esc to quit
+//! Runner::new(MouseButton::Left.click().sleep_ms(100))
+//!     .looping()
+//!     .bind("F6".parse().unwrap(), Action::Toggle)
+//!     .bind("F7".parse().unwrap(), Action::TogglePause)
+//!     .bind("Esc".parse().unwrap(), Action::Quit)
+//!     .run(keyboard, Simulator::new)
+//!     .unwrap();
+//! ```
+
+use std::{
+    collections::VecDeque,
+    io,
+    sync::{mpsc, Arc, Condvar, Mutex, MutexGuard},
+    thread,
+    time::Instant,
+};
+
+use crate::{
+    capture::evdev::EvdevRecorder,
+    chord::{Chord, ChordKey},
+    common_inputs::{Char, Key, MouseButton},
+    control::{Control, Controlled},
+    input_event::SetTo,
+    simulatable::Simulatable,
+    simulator::Simulate,
+    simulators::virtual_state::VirtualState,
+    timeline::{Event, Timed, Timeline},
+};
+
+#[cfg(test)]
+mod test;
+
+/// Something that produces input events as they happen.
+pub trait InputSource {
+    /// Wait for the next event, `None` when there will be no more.
+    fn next_event(&mut self) -> io::Result<Option<Event>>;
+}
+
+impl<R: io::Read> InputSource for EvdevRecorder<R> {
+    fn next_event(&mut self) -> io::Result<Option<Event>> {
+        EvdevRecorder::next_event(self)
+    }
+}
+
+/// Input source that plays a timeline in real time, for testing.
+#[derive(Debug, Clone)]
+pub struct ScriptedSource {
+    events: VecDeque<Timed>,
+    /// set on the first event
+    start: Option<Instant>,
+}
+
+impl ScriptedSource {
+    /// Produce events of the timeline when their offset has passed.
+    pub fn new(timeline: Timeline) -> ScriptedSource {
+        ScriptedSource {
+            events: timeline.events.into(),
+            start: None,
+        }
+    }
+
+    /// Produce events immediately.
+    pub fn events(events: impl IntoIterator<Item = Event>) -> ScriptedSource {
+        let mut timeline = Timeline::new();
+        for event in events {
+            timeline.push(event);
+        }
+        ScriptedSource::new(timeline)
+    }
+}
+
+impl InputSource for ScriptedSource {
+    fn next_event(&mut self) -> io::Result<Option<Event>> {
+        let Some(Timed { offset, event }) = self.events.pop_front() else {
+            return Ok(None);
+        };
+        let start = *self.start.get_or_insert_with(Instant::now);
+        if let Some(wait) = (start + offset).checked_duration_since(Instant::now()) {
+            thread::sleep(wait);
+        }
+        Ok(Some(event))
+    }
+}
+
+/// What a hotkey does to the simulatable of a [`Runner`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Start if it's not running
+    Start,
+    /// Pause at the next checkpoint, see [`control`](crate::control)
+    Pause,
+    Resume,
+    /// Pause if running, resume if paused
+    TogglePause,
+    /// Cancel and release every input it's holding
+    Abort,
+    /// Start if it's not running, abort if it is
+    Toggle,
+    /// Abort and stop listening
+    Quit,
+}
+
+/// Control of the running simulatable, shared between the listener and the worker thread.
+#[derive(Debug, Default)]
+struct Shared {
+    running: Mutex<Option<Control>>,
+    idle: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, Option<Control>> {
+        self.running
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn set_idle(&self) {
+        *self.lock() = None;
+        self.idle.notify_all();
+    }
+
+    fn wait_idle(&self) {
+        let mut running = self.lock();
+        while running.is_some() {
+            running = self
+                .idle
+                .wait(running)
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+    }
+}
+
+/// Sets the shared control idle when dropped,
+/// so the listener doesn't wait forever for a worker that panicked.
+struct IdleGuard<'a>(&'a Shared);
+
+impl Drop for IdleGuard<'_> {
+    fn drop(&mut self) {
+        self.0.set_idle();
+    }
+}
+
+/// Runs a simulatable in a background thread, controlled by hotkeys.
+///
+/// Hotkeys trigger when their key is pressed while their held keys are held,
+/// other keys may be held too.
+#[derive(Debug, Clone)]
+pub struct Runner<S> {
+    simulatable: S,
+    bindings: Vec<(Chord, Action)>,
+    looping: bool,
+}
+
+impl<S> Runner<S> {
+    pub fn new(simulatable: S) -> Runner<S> {
+        Runner {
+            simulatable,
+            bindings: Vec::new(),
+            looping: false,
+        }
+    }
+
+    pub fn bind(mut self, chord: Chord, action: Action) -> Runner<S> {
+        self.bindings.push((chord, action));
+        self
+    }
+
+    /// Repeat the simulatable until it's aborted instead of running it once.
+    pub fn looping(mut self) -> Runner<S> {
+        self.looping = true;
+        self
+    }
+
+    /// Listen to `source` until it ends or [`Action::Quit`] is triggered.
+    ///
+    /// The simulator is created by `simulator` in the background thread
+    /// because most simulators can't be sent between threads.
+    ///
+    /// When the source ends, a simulatable that is running once is waited for
+    /// and a looping or paused one is aborted.
+    pub fn run<Src, Smlt, F>(self, mut source: Src, simulator: F) -> io::Result<()>
+    where
+        Src: InputSource,
+        F: FnOnce() -> Smlt + Send + 'static,
+        S: for<'a> Simulatable<Controlled<&'a mut Smlt>> + Clone + Send + 'static,
+        Smlt: Simulate<SetTo<Key, bool>>
+            + Simulate<SetTo<MouseButton, bool>>
+            + Simulate<SetTo<Char, bool>>
+            + 'static,
+    {
+        let Runner {
+            simulatable,
+            bindings,
+            looping,
+        } = self;
+        let shared = Arc::new(Shared::default());
+        let (start, started) = mpsc::channel::<Control>();
+        let worker = {
+            let shared = shared.clone();
+            thread::spawn(move || {
+                // declared first so the channel is closed before a panic sets it idle
+                let _idle = IdleGuard(&shared);
+                let mut simulator = simulator();
+                for control in started {
+                    loop {
+                        let result = control.run_with(simulatable.clone(), &mut simulator);
+                        if !looping || result.is_err() || control.checkpoint().is_err() {
+                            break;
+                        }
+                    }
+                    shared.set_idle();
+                }
+            })
+        };
+
+        let mut pressed = VirtualState::new();
+        let result = loop {
+            let event = match source.next_event() {
+                Ok(Some(event)) => event,
+                Ok(None) => break Ok(()),
+                Err(e) => break Err(e),
+            };
+            let triggered = bindings
+                .iter()
+                .filter(|(chord, _)| triggers(chord, &event, &pressed))
+                .map(|(_, action)| *action)
+                .collect::<Vec<_>>();
+            event.run_with(&mut pressed);
+            let mut quit = false;
+            for action in triggered {
+                quit |= act(&shared, &start, action);
+            }
+            if quit {
+                break Ok(());
+            }
+        };
+
+        let paused = shared.lock().as_ref().is_some_and(Control::is_paused);
+        if looping || paused || result.is_err() {
+            act(&shared, &start, Action::Abort);
+        }
+        shared.wait_idle();
+        drop(start);
+        if let Err(panic) = worker.join() {
+            std::panic::resume_unwind(panic);
+        }
+        result
+    }
+}
+
+/// `event` is the press of the chord key while its held keys are pressed.
+fn triggers(chord: &Chord, event: &Event, pressed: &VirtualState) -> bool {
+    let is_key = match (&chord.key, event) {
+        (ChordKey::Key(key), Event::Key(e)) => e.to && e.input == *key,
+        (ChordKey::Char(c), Event::Char(e)) => e.to && e.input == *c,
+        _ => false,
+    };
+    is_key && chord.held.iter().all(|key| pressed.is_pressed(*key))
+}
+
+/// Apply an action, returns `true` on quit.
+fn act(shared: &Shared, start: &mpsc::Sender<Control>, action: Action) -> bool {
+    let mut running = shared.lock();
+    match (action, running.as_ref()) {
+        (Action::Start | Action::Toggle, None) => {
+            let control = Control::new();
+            // fails if the worker has panicked, it's resumed once the listener stops
+            if start.send(control.clone()).is_ok() {
+                *running = Some(control);
+            }
+        }
+        (Action::Pause, Some(control)) => control.pause(),
+        (Action::Resume, Some(control)) => control.resume(),
+        (Action::TogglePause, Some(control)) => {
+            if control.is_paused() {
+                control.resume()
+            } else {
+                control.pause()
+            }
+        }
+        (Action::Abort | Action::Toggle | Action::Quit, Some(control)) => control.cancel(),
+        _ => {}
+    }
+    action == Action::Quit
+}
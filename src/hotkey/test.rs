@@ -0,0 +1,117 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::prelude::*;
+use crate::simulator::Simulate;
+use crate::simulators::string_event_logger::StringEventLogger as S;
+use crate::timeline::{Event, Timeline};
+
+use super::{Action, Runner, ScriptedSource};
+
+/// Logger that can be read after the runner is done.
+#[derive(Debug, Clone, Default)]
+struct SharedLogger(Arc<Mutex<S>>);
+
+impl<E> Simulate<E> for SharedLogger
+where
+    S: Simulate<E>,
+{
+    fn simulate(&mut self, simulatable: E) {
+        self.0.lock().unwrap().simulate(simulatable)
+    }
+}
+
+fn press(key: Key) -> Event {
+    Event::from(key.down())
+}
+
+fn release(key: Key) -> Event {
+    Event::from(key.up())
+}
+
+#[test]
+fn hotkey_start_once() {
+    let logger = SharedLogger::default();
+    let source = ScriptedSource::events([
+        // not the chord yet
+        press(Key::F6),
+        release(Key::F6),
+        press(Key::Control),
+        press(Key::F6),
+        release(Key::F6),
+        release(Key::Control),
+    ]);
+    let simulator = logger.clone();
+    Runner::new(Key::Tab.click())
+        .bind("ctrl+F6".parse().unwrap(), Action::Start)
+        .run(source, move || simulator)
+        .unwrap();
+    let s = logger.0.lock().unwrap();
+    assert_eq!(
+        s.data,
+        vec![
+            format!("{:?}", Key::Tab.down()),
+            format!("{:?}", Key::Tab.up())
+        ]
+    );
+}
+
+#[test]
+fn hotkey_abort_releases() {
+    let logger = SharedLogger::default();
+    let mut script = Timeline::new();
+    script.push(press(Key::F6));
+    script.wait(Duration::from_millis(50));
+    script.push(press(Key::F7));
+    let simulator = logger.clone();
+    Runner::new(Char('a').click().sleep_ms(5).during(Key::Shift.down()))
+        .looping()
+        .bind("F6".parse().unwrap(), Action::Start)
+        .bind("F7".parse().unwrap(), Action::Abort)
+        .run(ScriptedSource::new(script), move || simulator)
+        .unwrap();
+    let s = logger.0.lock().unwrap();
+    assert!(s.data.len() > 4, "ran for a while: {:?}", s.data);
+    assert!(s.data.len() < 100, "stopped on abort: {:?}", s.data);
+    assert_eq!(s.data[s.data.len() - 1], format!("{:?}", Key::Shift.up()));
+}
+
+#[test]
+fn hotkey_quit_while_paused() {
+    let logger = SharedLogger::default();
+    let mut script = Timeline::new();
+    script.push(press(Key::F6));
+    script.wait(Duration::from_millis(30));
+    script.push(press(Key::F7));
+    script.wait(Duration::from_millis(30));
+    script.push(press(Key::Escape));
+    let simulator = logger.clone();
+    Runner::new(Key::Tab.click().sleep_ms(5).repeat(1000))
+        .bind("F6".parse().unwrap(), Action::Toggle)
+        .bind("F7".parse().unwrap(), Action::TogglePause)
+        .bind("Esc".parse().unwrap(), Action::Quit)
+        .run(ScriptedSource::new(script), move || simulator)
+        .unwrap();
+    let s = logger.0.lock().unwrap();
+    assert!(s.data.len() < 100, "stopped on quit: {:?}", s.data);
+    assert_eq!(s.data.len() % 2, 0, "every press is released: {:?}", s.data);
+}
+
+/// Simulator that panics on anything.
+struct Panicking;
+
+impl<E> Simulate<E> for Panicking {
+    fn simulate(&mut self, _: E) {
+        panic!("simulator failed");
+    }
+}
+
+#[test]
+#[should_panic(expected = "simulator failed")]
+fn hotkey_worker_panic() {
+    let source = ScriptedSource::events([press(Key::F6), release(Key::F6), press(Key::F6)]);
+    Runner::new(Key::Tab.click())
+        .bind("F6".parse().unwrap(), Action::Start)
+        .run(source, || Panicking)
+        .unwrap();
+}
@@ -1,6 +1,10 @@
 //! Module containing base input events.
 
-use crate::{simulatable::Simulatable, simulator::Simulate};
+use crate::{
+    estimate::{Estimate, Estimation},
+    simulatable::Simulatable,
+    simulator::Simulate,
+};
 use std::fmt;
 
 /// Trait for an event that is the inverse version of self.
@@ -19,6 +23,7 @@ pub trait Invert {
 /// An event of some input state is going to be setted to some value.
 /// The simulator must support this to be used.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SetTo<I, V> {
     pub input: I,
     pub to: V,
@@ -33,6 +38,12 @@ where
     }
 }
 
+impl<I, V> Estimate for SetTo<I, V> {
+    fn estimate(&self) -> Estimation {
+        Estimation::EVENT
+    }
+}
+
 impl<I> Invert for SetTo<I, bool> {
     type Output = SetTo<I, bool>;
 
@@ -55,6 +66,7 @@ where
 /// An event of some input state is going to be changed by some value.
 /// The simulator must support this to be used.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChangeBy<I, V> {
     pub input: I,
     pub by: V,
@@ -69,6 +81,12 @@ where
     }
 }
 
+impl<I, V> Estimate for ChangeBy<I, V> {
+    fn estimate(&self) -> Estimation {
+        Estimation::EVENT
+    }
+}
+
 impl<I, V> fmt::Display for ChangeBy<I, V>
 where
     I: fmt::Display,
@@ -80,6 +98,7 @@ where
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Execute<I> {
     pub input: I,
 }
@@ -93,6 +112,12 @@ where
     }
 }
 
+impl<I> Estimate for Execute<I> {
+    fn estimate(&self) -> Estimation {
+        Estimation::EVENT
+    }
+}
+
 impl<I> fmt::Display for Execute<I>
 where
     I: fmt::Display,
@@ -0,0 +1,493 @@
+//! Collection of built-in [`Layer`]s.
+//!
+//! ```
+//! # use kemuler::simulators::string_event_logger::StringEventLogger as Simulator;
+//! use kemuler::prelude::*;
+//! use kemuler::layers::{Delay, Log};
+//! use kemuler::simulators::{balance_checker::BalanceChecker, virtual_state::VirtualState};
+//!
+//! let mut s = Simulator::new()
+//!     .layer(VirtualState::new())
+//!     .layer(BalanceChecker::new())
+//!     .layer(Delay::from_ms(1))
+//!     .layer(Log::new(Vec::new()));
+//!
+//! Key::Alt.down().run_with(&mut s);
+//!
+//! // layers from the outside in
+//! let log = &s.layer;
+//! let checker = &s.inner.inner.layer;
+//! let state = &s.inner.inner.inner.layer;
+//! let logger = &s.inner.inner.inner.inner;
+//!
+//! assert_eq!(log.writer, b"SetTo { input: Alt, to: true }\n");
+//! assert!(!checker.report().is_ok());
+//! assert!(state.is_pressed(Key::Alt));
+//! assert_eq!(logger.data.len(), 1);
+//! ```
+//!
+//! [`VirtualState`](crate::simulators::virtual_state::VirtualState),
+//! [`SharedState`](crate::simulators::virtual_state::SharedState) and
+//! [`BalanceChecker`](crate::simulators::balance_checker::BalanceChecker)
+//! are layers too.
+
+use std::{collections::HashMap, fmt, io, time::Duration};
+
+#[cfg(feature = "spin_sleep")]
+use crate::combinator::SpinSleep;
+use crate::{
+    combinator::Sleep,
+    common_inputs::{Char, Key, MouseButton, MousePosition, MouseScroll, StrSequence},
+    input_event::*,
+    simulatable::Simulatable,
+    simulator::*,
+};
+
+#[cfg(test)]
+mod test;
+
+/// Write the `Debug` text of every simulatable, one per line, then pass it on.
+///
+/// Write errors are ignored so logging never stops a simulation.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Log<W> {
+    pub writer: W,
+}
+
+impl<W> Log<W> {
+    pub fn new(writer: W) -> Log<W> {
+        Log { writer }
+    }
+}
+
+impl Log<io::Stdout> {
+    pub fn stdout() -> Log<io::Stdout> {
+        Log::new(io::stdout())
+    }
+}
+
+impl Log<io::Stderr> {
+    pub fn stderr() -> Log<io::Stderr> {
+        Log::new(io::stderr())
+    }
+}
+
+impl<E, S, W> Layer<E, S> for Log<W>
+where
+    E: fmt::Debug,
+    S: Simulate<E>,
+    W: io::Write,
+{
+    fn handle(&mut self, simulatable: E, inner: &mut S) {
+        let _ = writeln!(self.writer, "{simulatable:?}");
+        inner.simulate(simulatable);
+    }
+}
+
+/// Pass every simulatable on and then sleep.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Delay(pub Duration);
+
+impl Delay {
+    pub fn from_ms(ms: u64) -> Delay {
+        Delay(Duration::from_millis(ms))
+    }
+}
+
+impl<E, S> Layer<E, S> for Delay
+where
+    S: Simulate<E>,
+    Sleep: Simulatable<S>,
+{
+    fn handle(&mut self, simulatable: E, inner: &mut S) {
+        inner.simulate(simulatable);
+        Sleep(self.0).run_with(inner);
+    }
+}
+
+/// A rule of [`Remap`]: replace the first input with the second one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rule {
+    Key(Key, Key),
+    MouseButton(MouseButton, MouseButton),
+    Char(Char, Char),
+}
+
+/// Replace inputs of `SetTo`, `ChangeBy` and `Execute` before passing them on.
+///
+/// Every input is mapped once so swapping two inputs works.
+/// Characters of a [`StrSequence`] are mapped by the char rules.
+///
+/// ```
+/// # use kemuler::simulators::string_event_logger::StringEventLogger as Simulator;
+/// # use kemuler::assert_event;
+/// use kemuler::prelude::*;
+/// use kemuler::layers::Remap;
+///
+/// // left handed mouse on a mac
+/// let remap = Remap::new()
+///     .swap_mouse_buttons(MouseButton::Left, MouseButton::Right)
+///     .key(Key::Control, Key::Meta);
+/// let mut s = Simulator::new().layer(remap);
+///
+/// Char('c').click().during(Key::Control.down()).run_with(&mut s);
+/// MouseButton::Left.down().run_with(&mut s);
+///
+/// let s = s.inner;
+/// assert_event!(s, 0, Key::Meta.down());
+/// assert_event!(s, 1, Char('c').down());
+/// assert_event!(s, 2, Char('c').up());
+/// assert_event!(s, 3, Key::Meta.up());
+/// assert_event!(s, 4, MouseButton::Right.down());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Remap {
+    pub keys: HashMap<Key, Key>,
+    pub mouse_buttons: HashMap<MouseButton, MouseButton>,
+    pub chars: HashMap<Char, Char>,
+}
+
+impl Remap {
+    pub fn new() -> Remap {
+        Remap::default()
+    }
+
+    /// Add a rule, replacing the previous rule of the same input.
+    pub fn rule(mut self, rule: Rule) -> Remap {
+        match rule {
+            Rule::Key(from, to) => self.keys.insert(from, to).map(drop),
+            Rule::MouseButton(from, to) => self.mouse_buttons.insert(from, to).map(drop),
+            Rule::Char(from, to) => self.chars.insert(from, to).map(drop),
+        };
+        self
+    }
+
+    pub fn key(self, from: Key, to: Key) -> Remap {
+        self.rule(Rule::Key(from, to))
+    }
+
+    pub fn mouse_button(self, from: MouseButton, to: MouseButton) -> Remap {
+        self.rule(Rule::MouseButton(from, to))
+    }
+
+    pub fn char(self, from: char, to: char) -> Remap {
+        self.rule(Rule::Char(Char(from), Char(to)))
+    }
+
+    pub fn swap_keys(self, a: Key, b: Key) -> Remap {
+        self.key(a, b).key(b, a)
+    }
+
+    pub fn swap_mouse_buttons(self, a: MouseButton, b: MouseButton) -> Remap {
+        self.mouse_button(a, b).mouse_button(b, a)
+    }
+}
+
+impl FromIterator<Rule> for Remap {
+    fn from_iter<T: IntoIterator<Item = Rule>>(iter: T) -> Self {
+        iter.into_iter().fold(Remap::new(), Remap::rule)
+    }
+}
+
+/// Input that can be mapped by [`Remap`].
+pub trait MapInput<I> {
+    fn map_input(&self, input: I) -> I;
+}
+
+impl MapInput<Key> for Remap {
+    fn map_input(&self, input: Key) -> Key {
+        self.keys.get(&input).copied().unwrap_or(input)
+    }
+}
+
+impl MapInput<MouseButton> for Remap {
+    fn map_input(&self, input: MouseButton) -> MouseButton {
+        self.mouse_buttons.get(&input).copied().unwrap_or(input)
+    }
+}
+
+impl MapInput<Char> for Remap {
+    fn map_input(&self, input: Char) -> Char {
+        self.chars.get(&input).copied().unwrap_or(input)
+    }
+}
+
+/// implement map input that doesn't change anything
+macro_rules! identity_map_input {
+    ($($ty:ty)*) => {
+        $(
+            impl MapInput<$ty> for Remap {
+                fn map_input(&self, input: $ty) -> $ty {
+                    input
+                }
+            }
+        )*
+    };
+}
+
+identity_map_input! {
+    MousePosition
+    MouseScroll
+}
+
+#[cfg(feature = "enigo")]
+identity_map_input! {
+    enigo::Key
+    enigo::MouseButton
+}
+
+impl<S> Layer<Sleep, S> for Remap
+where
+    S: Simulate<Sleep>,
+{
+    fn handle(&mut self, simulatable: Sleep, inner: &mut S) {
+        inner.simulate(simulatable)
+    }
+}
+
+#[cfg(feature = "spin_sleep")]
+impl<S> Layer<SpinSleep, S> for Remap
+where
+    S: Simulate<SpinSleep>,
+{
+    fn handle(&mut self, simulatable: SpinSleep, inner: &mut S) {
+        inner.simulate(simulatable)
+    }
+}
+
+impl<I, V, S> Layer<SetTo<I, V>, S> for Remap
+where
+    Remap: MapInput<I>,
+    S: Simulate<SetTo<I, V>>,
+{
+    fn handle(&mut self, simulatable: SetTo<I, V>, inner: &mut S) {
+        let SetTo { input, to } = simulatable;
+        inner.simulate(SetTo {
+            input: self.map_input(input),
+            to,
+        })
+    }
+}
+
+impl<I, V, S> Layer<ChangeBy<I, V>, S> for Remap
+where
+    Remap: MapInput<I>,
+    S: Simulate<ChangeBy<I, V>>,
+{
+    fn handle(&mut self, simulatable: ChangeBy<I, V>, inner: &mut S) {
+        let ChangeBy { input, by } = simulatable;
+        inner.simulate(ChangeBy {
+            input: self.map_input(input),
+            by,
+        })
+    }
+}
+
+impl<I, S> Layer<Execute<I>, S> for Remap
+where
+    Remap: MapInput<I>,
+    S: Simulate<Execute<I>>,
+{
+    fn handle(&mut self, simulatable: Execute<I>, inner: &mut S) {
+        inner.simulate(Execute {
+            input: self.map_input(simulatable.input),
+        })
+    }
+}
+
+/// Every character of the string is mapped by the char rules.
+impl<'a, S> Layer<Execute<StrSequence<'a>>, S> for Remap
+where
+    S: for<'b> Simulate<Execute<StrSequence<'b>>>,
+{
+    fn handle(&mut self, simulatable: Execute<StrSequence<'a>>, inner: &mut S) {
+        let text: String = simulatable
+            .input
+            .0
+            .chars()
+            .map(|c| self.map_input(Char(c)).0)
+            .collect();
+        inner.simulate(StrSequence(&text).execute())
+    }
+}
+
+/// Multiply the duration of every sleep by a factor
+/// and keep the time between events within a range.
+///
+/// A factor below 1 is faster and above 1 is slower.
+/// With `min`, a sleep is inserted before an event that would happen
+/// sooner than `min` after the previous one, even without a sleep between them.
+/// With `max`, sleeps between two events add up to at most `max`.
+/// Sleeps before the first event and after the last one are not lengthened.
+///
+/// The time since the last event is kept across runs.
+///
+/// ```
+/// # use kemuler::simulators::string_event_logger::StringEventLogger as Simulator;
+/// use std::time::Duration;
+/// use kemuler::prelude::*;
+/// use kemuler::layers::TimeScale;
+/// use kemuler::timeline::{Recorder, Timeline};
+///
+/// // twice as fast but never faster than 10 ms between events
+/// let scale = TimeScale::speed(2.0).min(Duration::from_millis(10));
+///
+/// let mut s = Recorder::new().layer(scale);
+/// (Key::Alt.click().sleep_ms(100), Key::Tab.click().sleep_ms(4))
+///     .seq()
+///     .run_with(&mut s);
+/// // 10 ms within each click, 50 ms between them and 2 ms at the end
+/// assert_eq!(s.inner.timeline().duration, Duration::from_millis(72));
+///
+/// // the same for recorded timelines
+/// let timeline = Timeline::compile(Key::Alt.click().sleep_ms(100)).scaled(&scale);
+/// assert_eq!(timeline.duration, Duration::from_millis(60));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeScale {
+    pub factor: f64,
+    /// Shortest time between two events after scaling
+    pub min: Option<Duration>,
+    /// Longest time between two events after scaling
+    pub max: Option<Duration>,
+    /// Time passed on since the last event, `None` before the first one
+    gap: Option<Duration>,
+}
+
+impl TimeScale {
+    /// # Panics
+    /// If `factor` is negative, infinite or NaN.
+    pub fn new(factor: f64) -> TimeScale {
+        assert!(
+            factor.is_finite() && factor >= 0.0,
+            "time scale factor must be finite and not negative, got {factor}"
+        );
+        TimeScale {
+            factor,
+            min: None,
+            max: None,
+            gap: None,
+        }
+    }
+
+    /// Scale by `1 / speed`, `speed(2.0)` is twice as fast.
+    ///
+    /// # Panics
+    /// If `speed` is not positive, infinite or NaN.
+    pub fn speed(speed: f64) -> TimeScale {
+        assert!(
+            speed.is_finite() && speed > 0.0,
+            "speed must be finite and positive, got {speed}"
+        );
+        TimeScale::new(1.0 / speed)
+    }
+
+    pub fn min(mut self, min: Duration) -> TimeScale {
+        self.min = Some(min);
+        self
+    }
+
+    pub fn max(mut self, max: Duration) -> TimeScale {
+        self.max = Some(max);
+        self
+    }
+
+    /// Scale a duration, at most `max`.
+    pub fn scale(&self, duration: Duration) -> Duration {
+        let duration = duration.mul_f64(self.factor);
+        match self.max {
+            Some(max) => duration.min(max),
+            None => duration,
+        }
+    }
+
+    /// Scale the time between two events and clamp it to `min` and `max`.
+    pub fn gap(&self, duration: Duration) -> Duration {
+        let duration = self.scale(duration);
+        match self.min {
+            Some(min) => duration.max(min),
+            None => duration,
+        }
+    }
+
+    /// Scale a sleep, capped so the gap since the last event stays within `max`.
+    fn sleep(&mut self, duration: Duration) -> Duration {
+        let Some(gap) = self.gap else {
+            return self.scale(duration);
+        };
+        let mut duration = duration.mul_f64(self.factor);
+        if let Some(max) = self.max {
+            duration = duration.min(max.saturating_sub(gap));
+        }
+        self.gap = Some(gap + duration);
+        duration
+    }
+
+    /// Sleep for what is left of `min` since the last event.
+    fn before_event<S>(&mut self, inner: &mut S)
+    where
+        S: Simulate<Sleep>,
+    {
+        if let (Some(min), Some(gap)) = (self.min, self.gap) {
+            if gap < min {
+                inner.simulate(Sleep(min - gap));
+            }
+        }
+        self.gap = Some(Duration::ZERO);
+    }
+}
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        TimeScale::new(1.0)
+    }
+}
+
+impl<S> Layer<Sleep, S> for TimeScale
+where
+    S: Simulate<Sleep>,
+{
+    fn handle(&mut self, simulatable: Sleep, inner: &mut S) {
+        inner.simulate(Sleep(self.sleep(simulatable.0)))
+    }
+}
+
+#[cfg(feature = "spin_sleep")]
+impl<S> Layer<SpinSleep, S> for TimeScale
+where
+    S: Simulate<SpinSleep>,
+{
+    fn handle(&mut self, simulatable: SpinSleep, inner: &mut S) {
+        inner.simulate(SpinSleep(self.sleep(simulatable.0)))
+    }
+}
+
+impl<I, V, S> Layer<SetTo<I, V>, S> for TimeScale
+where
+    S: Simulate<SetTo<I, V>> + Simulate<Sleep>,
+{
+    fn handle(&mut self, simulatable: SetTo<I, V>, inner: &mut S) {
+        self.before_event(inner);
+        inner.simulate(simulatable)
+    }
+}
+
+impl<I, V, S> Layer<ChangeBy<I, V>, S> for TimeScale
+where
+    S: Simulate<ChangeBy<I, V>> + Simulate<Sleep>,
+{
+    fn handle(&mut self, simulatable: ChangeBy<I, V>, inner: &mut S) {
+        self.before_event(inner);
+        inner.simulate(simulatable)
+    }
+}
+
+impl<I, S> Layer<Execute<I>, S> for TimeScale
+where
+    S: Simulate<Execute<I>> + Simulate<Sleep>,
+{
+    fn handle(&mut self, simulatable: Execute<I>, inner: &mut S) {
+        self.before_event(inner);
+        inner.simulate(simulatable)
+    }
+}
@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use crate::combinator::Sleep;
+use crate::prelude::*;
+use crate::timeline::Recorder;
+use crate::{assert_event, simulators::string_event_logger::StringEventLogger as S};
+
+use super::{Remap, TimeScale};
+
+#[test]
+fn remap_str_sequence() {
+    let mut s = S::new().layer(Remap::new().char('z', 'y').char('y', 'z'));
+    StrSequence("lazy").execute().run_with(&mut s);
+    Char('z').click().run_with(&mut s);
+    let s = s.inner;
+    assert_event!(s, 0, StrSequence("layz").execute());
+    assert_event!(s, 1, Char('y').down());
+}
+
+#[test]
+fn remap_swap_keys() {
+    let mut s = S::new().layer(Remap::new().swap_keys(Key::Control, Key::Meta));
+    (Key::Control.click(), Key::Meta.down(), Key::Alt.down())
+        .seq()
+        .run_with(&mut s);
+    let s = s.inner;
+    assert_event!(s, 0, Key::Meta.down());
+    assert_event!(s, 1, Key::Meta.up());
+    assert_event!(s, 2, Key::Control.down());
+    assert_event!(s, 3, Key::Alt.down());
+}
+
+#[test]
+fn remap_swap_mouse_buttons() {
+    let mut s =
+        S::new().layer(Remap::new().swap_mouse_buttons(MouseButton::Left, MouseButton::Right));
+    (
+        MouseButton::Left.down(),
+        MouseButton::Right.down(),
+        MouseButton::Middle.down(),
+    )
+        .seq()
+        .run_with(&mut s);
+    let s = s.inner;
+    assert_event!(s, 0, MouseButton::Right.down());
+    assert_event!(s, 1, MouseButton::Left.down());
+    assert_event!(s, 2, MouseButton::Middle.down());
+}
+
+#[test]
+fn remap_swap_same_pair() {
+    // rules replace each other, swapping the same pair again changes nothing
+    let remap = Remap::new()
+        .swap_keys(Key::Alt, Key::Shift)
+        .swap_keys(Key::Shift, Key::Alt);
+    let mut s = S::new().layer(remap);
+    (Key::Alt.down(), Key::Shift.down()).seq().run_with(&mut s);
+    let s = s.inner;
+    assert_event!(s, 0, Key::Shift.down());
+    assert_event!(s, 1, Key::Alt.down());
+}
+
+#[test]
+fn time_scale_gap() {
+    let scale = TimeScale::new(1.0)
+        .min(Duration::from_millis(5))
+        .max(Duration::from_millis(40));
+    let mut s = Recorder::new().layer(scale);
+    (
+        Sleep::from_ms(100),
+        Key::Alt.click(),
+        Sleep::from_ms(30),
+        Sleep::from_ms(30),
+        Key::Tab.down(),
+    )
+        .seq()
+        .run_with(&mut s);
+    // the gap is kept from the previous run
+    Key::Tab.up().run_with(&mut s);
+    let offsets: Vec<_> = s
+        .inner
+        .timeline()
+        .iter()
+        .map(|timed| timed.offset)
+        .collect();
+    assert_eq!(offsets, [40, 45, 85, 90].map(Duration::from_millis));
+}
@@ -136,12 +136,48 @@
 //! assert_eq!(s.data.len(), 8); // only 8 events has been passed to the simulator
 //! // sleep event is not logged but it should work :P
 //! ```
+//!
+//! ## Custom simulators
+//!
+//! Sleeps are passed to the simulator instead of sleeping on their own,
+//! so [`Sleep`](combinator::Sleep), and every combinator that sleeps,
+//! only runs with simulators that implement `Simulate<Sleep>`.
+//! This is a breaking change for simulators written before it,
+//! sleeping for real with [`control::sleep`] is a one liner
+//! that also wakes up when the simulation is cancelled.
+//! ```
+//! use kemuler::{combinator::Sleep, control, prelude::*, simulator::Simulate};
+//!
+//! struct MySimulator;
+//!
+//! impl Simulate<Sleep> for MySimulator {
+//!     fn simulate(&mut self, simulatable: Sleep) {
+//!         control::sleep(simulatable.0)
+//!     }
+//! }
+//!
+//! Sleep::from_ms(1).run_with(&mut MySimulator);
+//! ```
+//! With the `spin_sleep` feature, do the same for `SpinSleep` with `control::spin_sleep`.
 #![cfg_attr(all(doc, CHANNEL_NIGHTLY), feature(doc_auto_cfg))]
 
+pub mod capture;
+pub mod chord;
 pub mod combinator;
+pub mod control;
+pub mod dsl;
+pub mod estimate;
+pub mod hotkey;
 pub mod input_event;
+pub mod layers;
+#[cfg(feature = "macro_file")]
+pub mod macro_file;
+pub mod query;
+#[cfg(feature = "ctrlc")]
+pub mod recovery;
 pub mod simulatable;
 pub mod simulator;
+pub mod timeline;
 
 pub mod common_inputs;
 pub mod simulators;
@@ -154,4 +190,5 @@ pub mod prelude {
     pub use combinator::Combine;
     pub use common_inputs::*;
     pub use simulatable::Simulatable;
+    pub use simulator::SimulatorExt;
 }
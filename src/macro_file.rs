@@ -0,0 +1,377 @@
+//! Module for saving and loading macros as files
+//!
+//! A macro file is [JSON lines](https://jsonlines.org):
+//! a header with the format version and [`Metadata`],
+//! then one line per event with the time to wait before it in microseconds.
+//! A last line without an event is the time to wait after the last event.
+//!
+//! ```text
+//! {"format":"kemuler-macro","version":1,"metadata":{"author":"me"}}
+//! {"wait_us":0,"event":{"key":{"input":"Control","to":true}}}
+//! {"wait_us":100000,"event":{"key":{"input":"Control","to":false}}}
+//! {"wait_us":500}
+//! ```
+//!
+//! Files of other versions are rejected with [`MacroError::IncompatibleVersion`].
+//!
+//! ```
+//! # use kemuler::simulators::string_event_logger::StringEventLogger as Simulator;
+//! use kemuler::prelude::*;
+//! use kemuler::macro_file::{Macro, Metadata};
+//! use kemuler::timeline::Timeline;
+//!
+//! let timeline = Timeline::compile(Key::Alt.click().sleep_ms(100).repeat(2));
+//! let metadata = Metadata {
+//!     author: Some("me".to_owned()),
+//!     ..Metadata::default()
+//! };
+//! let mut file = Vec::new();
+//! Macro::new(timeline.clone())
+//!     .with_metadata(metadata)
+//!     .write_to(&mut file)
+//!     .unwrap();
+//!
+//! let loaded = Macro::read_from(&file[..]).unwrap();
+//! assert_eq!(loaded.metadata.author.as_deref(), Some("me"));
+//! assert_eq!(loaded.timeline, timeline);
+//!
+//! let mut s = Simulator::new();
+//! loaded.run_with(&mut s);
+//! assert_eq!(s.data.len(), 4);
+//! ```
+
+use std::{
+    error::Error,
+    fmt,
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "spin_sleep")]
+use crate::combinator::SpinSleep;
+use crate::{
+    combinator::Sleep,
+    common_inputs::{Char, Key, MouseButton, MousePosition, MouseScroll, StrSequence},
+    estimate::{Estimate, Estimation},
+    input_event::*,
+    simulatable::Simulatable,
+    simulator::Simulate,
+    timeline::{Event, Timeline},
+};
+
+#[cfg(test)]
+mod test;
+
+/// Value of `format` in the header.
+pub const FORMAT: &str = "kemuler-macro";
+
+/// Version written by this crate, and the only version it can read.
+pub const VERSION: u32 = 1;
+
+/// Information about a macro, every field is optional.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Metadata {
+    /// Screen size in pixels the macro was made for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub screen_size: Option<(u32, u32)>,
+    /// Keyboard layout the macro was made for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layout: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// First line of a file.
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    format: String,
+    version: u32,
+    #[serde(default)]
+    metadata: Metadata,
+}
+
+/// Only what's needed to check the version,
+/// so newer headers are not rejected for the wrong reason.
+#[derive(Debug, Deserialize)]
+struct VersionHeader {
+    format: String,
+    version: u32,
+}
+
+/// Every line after the header.
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    /// Time to wait before the event
+    wait_us: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    event: Option<Event>,
+}
+
+#[derive(Debug)]
+pub enum MacroError {
+    Io(io::Error),
+    /// A line is not valid, lines start at 1
+    Json {
+        line: usize,
+        error: serde_json::Error,
+    },
+    /// There is no header
+    Empty,
+    /// The header is not of a macro file, contains the format found
+    NotAMacro(String),
+    IncompatibleVersion {
+        found: u32,
+        supported: u32,
+    },
+}
+
+impl fmt::Display for MacroError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MacroError::Io(e) => write!(f, "{e}"),
+            MacroError::Json { line, error } => write!(f, "line {line}: {error}"),
+            MacroError::Empty => write!(f, "empty macro file"),
+            MacroError::NotAMacro(format) => {
+                write!(f, "expected format \"{FORMAT}\", found \"{format}\"")
+            }
+            MacroError::IncompatibleVersion { found, supported } => write!(
+                f,
+                "incompatible macro file version {found}, only version {supported} is supported"
+            ),
+        }
+    }
+}
+
+impl Error for MacroError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MacroError::Io(e) => Some(e),
+            MacroError::Json { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for MacroError {
+    fn from(e: io::Error) -> Self {
+        MacroError::Io(e)
+    }
+}
+
+/// A timeline with metadata, as saved in a file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Macro {
+    pub metadata: Metadata,
+    pub timeline: Timeline,
+}
+
+impl Macro {
+    pub fn new(timeline: Timeline) -> Macro {
+        Macro {
+            metadata: Metadata::default(),
+            timeline,
+        }
+    }
+
+    pub fn with_metadata(mut self, metadata: Metadata) -> Macro {
+        self.metadata = metadata;
+        self
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Macro, MacroError> {
+        Macro::read_from(BufReader::new(File::open(path)?))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), MacroError> {
+        self.write_to(BufWriter::new(File::create(path)?))
+    }
+
+    pub fn read_from(reader: impl BufRead) -> Result<Macro, MacroError> {
+        let mut lines = reader.lines().enumerate().map(|(i, line)| (i + 1, line));
+        let (_, header) = lines.next().ok_or(MacroError::Empty)?;
+        let header = header?;
+        let version: VersionHeader = parse(1, &header)?;
+        if version.format != FORMAT {
+            return Err(MacroError::NotAMacro(version.format));
+        }
+        if version.version != VERSION {
+            return Err(MacroError::IncompatibleVersion {
+                found: version.version,
+                supported: VERSION,
+            });
+        }
+        let header: Header = parse(1, &header)?;
+
+        let mut timeline = Timeline::new();
+        for (line, text) in lines {
+            let text = text?;
+            if text.trim().is_empty() {
+                continue;
+            }
+            let entry: Entry = parse(line, &text)?;
+            timeline.wait(Duration::from_micros(entry.wait_us));
+            if let Some(event) = entry.event {
+                timeline.push(event);
+            }
+        }
+        Ok(Macro {
+            metadata: header.metadata,
+            timeline,
+        })
+    }
+
+    pub fn write_to(&self, writer: impl Write) -> Result<(), MacroError> {
+        let mut writer = MacroWriter::new(writer, self.metadata.clone())?;
+        let mut now = Duration::ZERO;
+        for timed in &self.timeline {
+            writer.wait(timed.offset.saturating_sub(now));
+            writer.push(timed.event.clone());
+            now = now.max(timed.offset);
+        }
+        writer.wait(self.timeline.duration.saturating_sub(now));
+        writer.finish()?;
+        Ok(())
+    }
+}
+
+fn parse<'a, T: Deserialize<'a>>(line: usize, text: &'a str) -> Result<T, MacroError> {
+    serde_json::from_str(text).map_err(|error| MacroError::Json { line, error })
+}
+
+impl<Smlt> Simulatable<Smlt> for Macro
+where
+    Timeline: Simulatable<Smlt>,
+{
+    fn run_with(self, simulator: &mut Smlt) {
+        self.timeline.run_with(simulator)
+    }
+}
+
+impl Estimate for Macro {
+    fn estimate(&self) -> Estimation {
+        self.timeline.estimate()
+    }
+}
+
+/// A simulator that doesn't simulate anything
+/// but writes what it has been given as a macro file.
+///
+/// Use it with [`Tee`](crate::simulators::tee::Tee) to save a macro while running it.
+/// Errors from the writer stop the output,
+/// the first one is returned by [`MacroWriter::finish`].
+///
+/// Implemented simulatables:
+/// - every [`Event`]
+/// - `combinator::Sleep` (doesn't sleep)
+/// - `combinator::SpinSleep` (doesn't sleep)
+#[derive(Debug)]
+pub struct MacroWriter<W: Write> {
+    writer: W,
+    /// time to wait before the next event
+    wait: Duration,
+    error: Option<MacroError>,
+}
+
+impl<W: Write> MacroWriter<W> {
+    /// Write the header.
+    pub fn new(mut writer: W, metadata: Metadata) -> Result<MacroWriter<W>, MacroError> {
+        let header = Header {
+            format: FORMAT.to_owned(),
+            version: VERSION,
+            metadata,
+        };
+        write_line(&mut writer, &header)?;
+        Ok(MacroWriter {
+            writer,
+            wait: Duration::ZERO,
+            error: None,
+        })
+    }
+
+    /// Write the time to wait after the last event
+    /// then return the writer, or the first error.
+    pub fn finish(mut self) -> Result<W, MacroError> {
+        if !self.wait.is_zero() {
+            self.write_entry(None);
+        }
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn wait(&mut self, duration: Duration) {
+        self.wait += duration;
+    }
+
+    fn push(&mut self, event: Event) {
+        self.write_entry(Some(event))
+    }
+
+    fn write_entry(&mut self, event: Option<Event>) {
+        if self.error.is_some() {
+            return;
+        }
+        let wait_us = self.wait.as_micros() as u64;
+        // keep what's shorter than a microsecond for the next entry
+        self.wait -= Duration::from_micros(wait_us);
+        if let Err(error) = write_line(&mut self.writer, &Entry { wait_us, event }) {
+            self.error = Some(error);
+        }
+    }
+}
+
+fn write_line(writer: &mut impl Write, value: &impl Serialize) -> Result<(), MacroError> {
+    serde_json::to_writer(&mut *writer, value).map_err(io::Error::from)?;
+    writer.write_all(b"\n")?;
+    Ok(())
+}
+
+/// implement macro writer for each event
+macro_rules! impl_macro_writer {
+    ($($ty:ty)*) => {
+        $(
+            impl<W: Write> Simulate<$ty> for MacroWriter<W> {
+                fn simulate(&mut self, simulatable: $ty) {
+                    self.push(simulatable.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_macro_writer! {
+    SetTo<Key, bool>
+    SetTo<MouseButton, bool>
+    SetTo<Char, bool>
+    SetTo<MousePosition, (i32, i32)>
+    ChangeBy<MousePosition, (i32, i32)>
+    ChangeBy<MouseScroll, (i32, i32)>
+}
+
+impl<'a, W: Write> Simulate<Execute<StrSequence<'a>>> for MacroWriter<W> {
+    fn simulate(&mut self, simulatable: Execute<StrSequence<'a>>) {
+        self.push(simulatable.into())
+    }
+}
+
+impl<W: Write> Simulate<Sleep> for MacroWriter<W> {
+    fn simulate(&mut self, simulatable: Sleep) {
+        self.wait(simulatable.0)
+    }
+}
+
+#[cfg(feature = "spin_sleep")]
+impl<W: Write> Simulate<SpinSleep> for MacroWriter<W> {
+    fn simulate(&mut self, simulatable: SpinSleep) {
+        self.wait(simulatable.0)
+    }
+}
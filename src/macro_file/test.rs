@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use crate::combinator::Sleep;
+use crate::prelude::*;
+use crate::simulators::tee::Tee;
+use crate::timeline::{Recorder, Timeline};
+
+use super::{Macro, MacroError, MacroWriter, Metadata};
+
+#[test]
+fn macro_file_text() {
+    let timeline = Timeline::compile(
+        (
+            Key::Control.down(),
+            Sleep::from_ms(100),
+            MousePosition.move_by(-1, 2),
+            StrSequence("hi").execute(),
+            Sleep(Duration::from_micros(500)),
+        )
+            .seq(),
+    );
+    let metadata = Metadata {
+        screen_size: Some((1920, 1080)),
+        ..Metadata::default()
+    };
+    let mut file = Vec::new();
+    Macro::new(timeline)
+        .with_metadata(metadata)
+        .write_to(&mut file)
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(file).unwrap(),
+        concat!(
+            r#"{"format":"kemuler-macro","version":1,"metadata":{"screen_size":[1920,1080]}}"#,
+            "\n",
+            r#"{"wait_us":0,"event":{"key":{"input":"Control","to":true}}}"#,
+            "\n",
+            r#"{"wait_us":100000,"event":{"move_by":{"input":null,"by":[-1,2]}}}"#,
+            "\n",
+            r#"{"wait_us":0,"event":{"type":"hi"}}"#,
+            "\n",
+            r#"{"wait_us":500}"#,
+            "\n",
+        )
+    );
+}
+
+#[test]
+fn macro_file_version() {
+    let file = r#"{"format":"kemuler-macro","version":2,"metadata":{"new":"field"}}"#;
+    assert!(matches!(
+        Macro::read_from(file.as_bytes()),
+        Err(MacroError::IncompatibleVersion {
+            found: 2,
+            supported: 1
+        })
+    ));
+    let file = r#"{"format":"something-else","version":1}"#;
+    assert!(matches!(
+        Macro::read_from(file.as_bytes()),
+        Err(MacroError::NotAMacro(format)) if format == "something-else"
+    ));
+    assert!(matches!(Macro::read_from(&b""[..]), Err(MacroError::Empty)));
+    let file = "{\"format\":\"kemuler-macro\",\"version\":1}\n{\"wait_us\":\"x\"}";
+    assert!(matches!(
+        Macro::read_from(file.as_bytes()),
+        Err(MacroError::Json { line: 2, .. })
+    ));
+}
+
+#[test]
+fn macro_file_writer() {
+    let x = (
+        MouseButton::Left.click(),
+        Sleep::from_ms(1),
+        MouseScroll.scroll_by(0, 3),
+    )
+        .seq()
+        .repeat(2);
+    let mut recorder = Recorder::new();
+    let mut writer = MacroWriter::new(Vec::new(), Metadata::default()).unwrap();
+    x.run_with(&mut Tee(&mut recorder, &mut writer));
+    let file = writer.finish().unwrap();
+
+    let loaded = Macro::read_from(&file[..]).unwrap();
+    assert_eq!(loaded.metadata, Metadata::default());
+    assert_eq!(&loaded.timeline, recorder.timeline());
+}
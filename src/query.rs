@@ -0,0 +1,472 @@
+//! Module for reading back the state of a simulator
+//!
+//! [`Simulate`] only sends inputs to a simulator,
+//! [`Query`] asks it a [`Question`] about its state.
+//! A simulator may not know the answer, in which case it returns `None`.
+//!
+//! Combinators such as [`Combine::when`] and [`Combine::if_else`]
+//! ask their question when they are simulated.
+//! [`ask`] builds what to simulate from the answer.
+//! Layers answer before the simulator they wrap, see [`QueryLayer`].
+//!
+//! Built-in simulators answer `None` to what they don't know.
+//! Layer a simulator that doesn't implement [`Query`] with [`NoQuery`]
+//! to run them anyway, or implement it for every question:
+//!
+//! ```
+//! use kemuler::query::{Query, Question};
+//!
+//! struct MySimulator;
+//!
+//! impl<Q: Question> Query<Q> for MySimulator {
+//!     fn query(&self, _: Q) -> Option<Q::Answer> {
+//!         None
+//!     }
+//! }
+//! ```
+//!
+//! ```
+//! # use kemuler::simulators::string_event_logger::StringEventLogger as Simulator;
+//! # use kemuler::assert_event;
+//! use kemuler::prelude::*;
+//! use kemuler::query::{ask, IsPressed, Query, ScreenSize};
+//!
+//! let mut s = Simulator::new().tracked();
+//! MousePosition.move_to(100, 800).run_with(&mut s);
+//! assert_eq!(s.query(MousePosition), Some((100, 800)));
+//!
+//! // scroll down in the top half, up in the bottom half
+//! let scroll = MouseScroll
+//!     .scroll_by(0, 1)
+//!     .if_else(MousePosition, |(_, y)| y < 540, MouseScroll.scroll_by(0, -1));
+//! scroll.run_with(&mut s);
+//!
+//! Key::Tab.click().when(IsPressed(Key::Shift), |pressed| pressed).run_with(&mut s);
+//! let logger = s.into_inner();
+//! assert_event!(logger, 1, MouseScroll.scroll_by(0, -1));
+//! assert_eq!(logger.data.len(), 2);
+//!
+//! // the logger doesn't know where the mouse is, so the condition is false
+//! let mut s = Simulator::new();
+//! Key::Tab.click().when(MousePosition, |_| true).run_with(&mut s);
+//! assert!(s.data.is_empty());
+//!
+//! // move to the middle of the screen, or stay if the size is not known
+//! let center = ask(ScreenSize, |size| {
+//!     size.map(|(w, h)| MousePosition.move_to(w as i32 / 2, h as i32 / 2))
+//! });
+//! center.run_with(&mut s);
+//! assert!(s.data.is_empty());
+//! ```
+//!
+//! [`Combine::when`]: crate::combinator::Combine::when
+//! [`Combine::if_else`]: crate::combinator::Combine::if_else
+
+use std::fmt;
+
+#[cfg(feature = "macro_file")]
+use crate::macro_file::MacroWriter;
+#[cfg(any(test, doctest, feature = "test"))]
+use crate::simulators::string_event_logger::StringEventLogger;
+use crate::{
+    common_inputs::{
+        screen::{DisplayLayout, ScreenSized},
+        MousePosition,
+    },
+    control::Control,
+    layers::{Delay, Log, Remap, TimeScale},
+    simulatable::Simulatable,
+    simulator::{Layer, Layered, Simulate},
+    simulators::{
+        balance_checker::BalanceChecker,
+        dry_run::DryRun,
+        tee::{FanOut, Tee},
+        virtual_state::{Button, SharedState, VirtualState},
+    },
+    timeline::Recorder,
+};
+
+#[cfg(test)]
+mod test;
+
+/// Something that can be asked to a simulator.
+pub trait Question {
+    type Answer;
+}
+
+/// Simulator that can answer a question about its state.
+pub trait Query<Q: Question> {
+    /// `None` if the answer is not known.
+    fn query(&self, question: Q) -> Option<Q::Answer>;
+}
+
+/// Where the mouse cursor is, in pixels.
+impl Question for MousePosition {
+    type Answer = (i32, i32);
+}
+
+/// Size of the main display in pixels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ScreenSize;
+
+impl Question for ScreenSize {
+    type Answer = (u32, u32);
+}
+
+impl fmt::Display for ScreenSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "screen size")
+    }
+}
+
+/// Monitors and where they are, see [`DisplayLayout`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Displays;
+
+impl Question for Displays {
+    type Answer = DisplayLayout;
+}
+
+impl fmt::Display for Displays {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "displays")
+    }
+}
+
+/// Is an input being held down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IsPressed<B>(pub B);
+
+impl<B: Into<Button>> Question for IsPressed<B> {
+    type Answer = bool;
+}
+
+impl<B: fmt::Display> fmt::Display for IsPressed<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "is {} pressed", self.0)
+    }
+}
+
+impl Query<MousePosition> for VirtualState {
+    fn query(&self, _: MousePosition) -> Option<(i32, i32)> {
+        Some(self.mouse_position())
+    }
+}
+
+impl<B: Into<Button>> Query<IsPressed<B>> for VirtualState {
+    fn query(&self, question: IsPressed<B>) -> Option<bool> {
+        Some(self.is_pressed(question.0))
+    }
+}
+
+/// The state doesn't know about the screen.
+impl Query<ScreenSize> for VirtualState {
+    fn query(&self, _: ScreenSize) -> Option<(u32, u32)> {
+        None
+    }
+}
+
+/// The state doesn't know about the screen.
+impl Query<Displays> for VirtualState {
+    fn query(&self, _: Displays) -> Option<DisplayLayout> {
+        None
+    }
+}
+
+/// implement query for simulators that don't know anything, every answer is `None`
+macro_rules! no_query {
+    ($( [$($g:ident),*] $simulator:ty ),* $(,)?) => {
+        $(
+            impl<Q: Question, $($g,)*> Query<Q> for $simulator {
+                fn query(&self, _: Q) -> Option<Q::Answer> {
+                    None
+                }
+            }
+        )*
+    };
+}
+
+no_query! {
+    [W] DryRun<W>,
+    [] Recorder,
+    [] BalanceChecker,
+}
+
+#[cfg(feature = "macro_file")]
+impl<Q: Question, W: std::io::Write> Query<Q> for MacroWriter<W> {
+    fn query(&self, _: Q) -> Option<Q::Answer> {
+        None
+    }
+}
+
+#[cfg(any(test, doctest, feature = "test"))]
+no_query! {
+    [] StringEventLogger,
+}
+
+impl<Q> Query<Q> for SharedState
+where
+    Q: Question,
+    VirtualState: Query<Q>,
+{
+    fn query(&self, question: Q) -> Option<Q::Answer> {
+        Query::query(&*self.lock(), question)
+    }
+}
+
+impl<Q, S> Query<Q> for &S
+where
+    Q: Question,
+    S: Query<Q> + ?Sized,
+{
+    fn query(&self, question: Q) -> Option<Q::Answer> {
+        (**self).query(question)
+    }
+}
+
+impl<Q, S> Query<Q> for &mut S
+where
+    Q: Question,
+    S: Query<Q> + ?Sized,
+{
+    fn query(&self, question: Q) -> Option<Q::Answer> {
+        (**self).query(question)
+    }
+}
+
+/// [`Layer`] that can answer a question, or pass it on to the inner simulator.
+///
+/// A layer without state to answer from forwards every question:
+///
+/// ```
+/// # use kemuler::simulator::Layer;
+/// use kemuler::query::{Query, QueryLayer, Question};
+///
+/// struct Nothing;
+/// # impl<E, S> Layer<E, S> for Nothing {
+/// #     fn handle(&mut self, _: E, _: &mut S) {}
+/// # }
+///
+/// impl<Q: Question, S: Query<Q>> QueryLayer<Q, S> for Nothing {
+///     fn query(&self, question: Q, inner: &S) -> Option<Q::Answer> {
+///         inner.query(question)
+///     }
+/// }
+/// ```
+///
+/// [`Layer`]: crate::simulator::Layer
+pub trait QueryLayer<Q: Question, S> {
+    /// Answer `question` on behalf of `inner`.
+    fn query(&self, question: Q, inner: &S) -> Option<Q::Answer>;
+}
+
+/// Answered by the layer, see [`QueryLayer`].
+impl<Q, L, S> Query<Q> for Layered<L, S>
+where
+    Q: Question,
+    L: QueryLayer<Q, S>,
+{
+    fn query(&self, question: Q) -> Option<Q::Answer> {
+        self.layer.query(question, &self.inner)
+    }
+}
+
+impl<S> QueryLayer<MousePosition, S> for VirtualState {
+    fn query(&self, question: MousePosition, _: &S) -> Option<(i32, i32)> {
+        Query::query(self, question)
+    }
+}
+
+impl<B: Into<Button>, S> QueryLayer<IsPressed<B>, S> for VirtualState {
+    fn query(&self, question: IsPressed<B>, _: &S) -> Option<bool> {
+        Query::query(self, question)
+    }
+}
+
+impl<S: Query<ScreenSize>> QueryLayer<ScreenSize, S> for VirtualState {
+    fn query(&self, question: ScreenSize, inner: &S) -> Option<(u32, u32)> {
+        inner.query(question)
+    }
+}
+
+impl<S: Query<Displays>> QueryLayer<Displays, S> for VirtualState {
+    fn query(&self, question: Displays, inner: &S) -> Option<DisplayLayout> {
+        inner.query(question)
+    }
+}
+
+impl<Q, S> QueryLayer<Q, S> for SharedState
+where
+    Q: Question,
+    VirtualState: QueryLayer<Q, S>,
+{
+    fn query(&self, question: Q, inner: &S) -> Option<Q::Answer> {
+        QueryLayer::query(&*self.lock(), question, inner)
+    }
+}
+
+/// implement query layer for layers that pass every question on
+macro_rules! forward_query_layer {
+    ($( [$($g:ident),*] $layer:ty ),* $(,)?) => {
+        $(
+            impl<Q, S, $($g,)*> QueryLayer<Q, S> for $layer
+            where
+                Q: Question,
+                S: Query<Q>,
+            {
+                fn query(&self, question: Q, inner: &S) -> Option<Q::Answer> {
+                    inner.query(question)
+                }
+            }
+        )*
+    };
+}
+
+impl<S> QueryLayer<ScreenSize, S> for ScreenSized {
+    fn query(&self, _: ScreenSize, _: &S) -> Option<(u32, u32)> {
+        Some(self.layout.primary()?.size)
+    }
+}
+
+impl<S> QueryLayer<Displays, S> for ScreenSized {
+    fn query(&self, _: Displays, _: &S) -> Option<DisplayLayout> {
+        Some(self.layout.clone())
+    }
+}
+
+impl<S: Query<MousePosition>> QueryLayer<MousePosition, S> for ScreenSized {
+    fn query(&self, question: MousePosition, inner: &S) -> Option<(i32, i32)> {
+        inner.query(question)
+    }
+}
+
+impl<B, S> QueryLayer<IsPressed<B>, S> for ScreenSized
+where
+    B: Into<Button>,
+    S: Query<IsPressed<B>>,
+{
+    fn query(&self, question: IsPressed<B>, inner: &S) -> Option<bool> {
+        inner.query(question)
+    }
+}
+
+forward_query_layer! {
+    [W] Log<W>,
+    [] Delay,
+    [] Remap,
+    [] TimeScale,
+    [] BalanceChecker,
+    [] Control,
+}
+
+/// Answered by the first simulator.
+impl<Q, A, B> Query<Q> for Tee<A, B>
+where
+    Q: Question,
+    A: Query<Q>,
+{
+    fn query(&self, question: Q) -> Option<Q::Answer> {
+        self.0.query(question)
+    }
+}
+
+/// Answered by the first simulator, `None` if there is none.
+impl<Q, S> Query<Q> for FanOut<Vec<S>>
+where
+    Q: Question,
+    S: Query<Q>,
+{
+    fn query(&self, question: Q) -> Option<Q::Answer> {
+        self.0.first()?.query(question)
+    }
+}
+
+/// implement query for fan out of each tuple size, answered by the first simulator
+macro_rules! fan_out_query_impl {
+    ($( ($($g:ident),*) )*) => {
+        $(
+            impl<Q, S0, $($g,)*> Query<Q> for FanOut<(S0, $($g,)*)>
+            where
+                Q: Question,
+                S0: Query<Q>,
+            {
+                fn query(&self, question: Q) -> Option<Q::Answer> {
+                    self.0 .0.query(question)
+                }
+            }
+        )*
+    };
+}
+
+fan_out_query_impl! {
+    ()
+    (S1)
+    (S1, S2)
+    (S1, S2, S3)
+    (S1, S2, S3, S4)
+    (S1, S2, S3, S4, S5)
+    (S1, S2, S3, S4, S5, S6)
+    (S1, S2, S3, S4, S5, S6, S7)
+    (S1, S2, S3, S4, S5, S6, S7, S8)
+    (S1, S2, S3, S4, S5, S6, S7, S8, S9)
+    (S1, S2, S3, S4, S5, S6, S7, S8, S9, S10)
+    (S1, S2, S3, S4, S5, S6, S7, S8, S9, S10, S11)
+}
+
+/// Layer for a simulator that doesn't support queries,
+/// every question is answered with `None`.
+///
+/// Every simulatable is passed on as is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct NoQuery;
+
+impl<E, S> Layer<E, S> for NoQuery
+where
+    S: Simulate<E>,
+{
+    fn handle(&mut self, simulatable: E, inner: &mut S) {
+        inner.simulate(simulatable)
+    }
+}
+
+impl<Q: Question, S> QueryLayer<Q, S> for NoQuery {
+    fn query(&self, _: Q, _: &S) -> Option<Q::Answer> {
+        None
+    }
+}
+
+/// Build what to simulate from the answer to a question,
+/// asked when this is simulated.
+pub fn ask<Q, F, S>(question: Q, build: F) -> Ask<Q, F>
+where
+    Q: Question,
+    F: FnOnce(Option<Q::Answer>) -> S,
+{
+    Ask { question, build }
+}
+
+/// Simulate what is built from the answer to a question.
+/// Created by [`ask`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ask<Q, F> {
+    pub question: Q,
+    pub build: F,
+}
+
+impl<Q: fmt::Display, F> fmt::Display for Ask<Q, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[ask ({})]", self.question)
+    }
+}
+
+impl<Q, F, S, Smlt> Simulatable<Smlt> for Ask<Q, F>
+where
+    Q: Question,
+    F: FnOnce(Option<Q::Answer>) -> S,
+    S: Simulatable<Smlt>,
+    Smlt: Query<Q>,
+{
+    fn run_with(self, simulator: &mut Smlt) {
+        (self.build)(simulator.query(self.question)).run_with(simulator)
+    }
+}
@@ -0,0 +1,102 @@
+use crate::control::Control;
+use crate::layers::{Delay, Log};
+use crate::prelude::*;
+use crate::simulator::Simulate;
+use crate::simulators::{
+    string_event_logger::StringEventLogger as S,
+    tee::{FanOut, Tee},
+    virtual_state::{SharedState, VirtualState},
+};
+
+use super::{ask, IsPressed, NoQuery, Query, ScreenSize};
+
+/// Logger on a screen of a known size.
+#[derive(Debug, Default)]
+struct Screen(S);
+
+impl<E> Simulate<E> for Screen
+where
+    S: Simulate<E>,
+{
+    fn simulate(&mut self, simulatable: E) {
+        self.0.simulate(simulatable)
+    }
+}
+
+impl Query<ScreenSize> for Screen {
+    fn query(&self, _: ScreenSize) -> Option<(u32, u32)> {
+        Some((1920, 1080))
+    }
+}
+
+fn mouse_position(s: impl Query<MousePosition>) -> Option<(i32, i32)> {
+    s.query(MousePosition)
+}
+
+#[test]
+fn query_forwarding() {
+    let mut state = VirtualState::with_mouse_position(5, 6);
+    assert_eq!(mouse_position(&mut state), Some((5, 6)));
+    assert_eq!(
+        Tee(&state, S::new().layer(NoQuery)).query(MousePosition),
+        Some((5, 6))
+    );
+    assert_eq!(
+        Tee(S::new().layer(NoQuery), &state).query(MousePosition),
+        None
+    );
+    assert_eq!(
+        FanOut((&state, S::new(), S::new())).query(MousePosition),
+        Some((5, 6))
+    );
+    assert_eq!(
+        FanOut(Vec::<VirtualState>::new()).query(MousePosition),
+        None
+    );
+    assert_eq!(
+        Screen::default().layer(Delay::from_ms(1)).query(ScreenSize),
+        Some((1920, 1080))
+    );
+
+    // the state answers about inputs, the inner simulator about the screen
+    let mut s = Screen::default().tracked();
+    Key::Shift.down().run_with(&mut s);
+    assert_eq!(s.query(IsPressed(Key::Shift)), Some(true));
+    assert_eq!(s.query(ScreenSize), Some((1920, 1080)));
+}
+
+#[test]
+fn query_layer_answers_first() {
+    let mut s = Screen::default()
+        .layer(VirtualState::new())
+        .layer(Log::new(Vec::new()))
+        .layer(SharedState::default());
+    MousePosition.move_to(3, 4).run_with(&mut s);
+    Key::Alt.down().run_with(&mut s);
+    // the outermost state answers, the inner one is never asked
+    s.inner.inner.layer = VirtualState::new();
+    assert_eq!(s.query(MousePosition), Some((3, 4)));
+    assert_eq!(s.query(IsPressed(Key::Alt)), Some(true));
+    assert_eq!(s.inner.query(IsPressed(Key::Alt)), Some(false));
+    assert_eq!(s.query(ScreenSize), Some((1920, 1080)));
+}
+
+#[test]
+fn query_ask() {
+    let center = || {
+        ask(ScreenSize, |size| {
+            size.map(|(w, h)| MousePosition.move_to(w as i32 / 2, h as i32 / 2))
+        })
+    };
+    let mut s = Screen::default();
+    let control = Control::new();
+    control.run_with(center(), &mut s).unwrap();
+    assert_eq!(
+        s.0.data,
+        vec![format!("{:?}", MousePosition.move_to(960, 540))]
+    );
+
+    let mut s = S::new().layer(NoQuery);
+    center().run_with(&mut s);
+    assert!(s.inner.data.is_empty());
+}
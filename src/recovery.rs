@@ -0,0 +1,79 @@
+//! Bring the system back to a neutral state when interrupted.
+//!
+//! A macro that is stopped half way, by Ctrl-C for example,
+//! may leave keys and mouse buttons held down.
+//! Track what is being held with a [`SharedState`]
+//! and let [`release_on_interrupt`] release them before the process exits.
+//!
+//! ```no_run
+//! # use kemuler::simulators::string_event_logger::StringEventLogger as Simulator;
+//! use kemuler::prelude::*;
+//! use kemuler::recovery::release_on_interrupt;
+//! use kemuler::simulators::virtual_state::SharedState;
+//!
+//! let state = SharedState::default();
+//! release_on_interrupt(state.clone(), Simulator::new).unwrap();
+//!
+//! let mut s = Simulator::new().layer(state);
+//! MouseButton::Left.click().sleep_ms(100).repeat(1000).run_with(&mut s);
+//! ```
+//!
+//! [`release_on_panic`] does the same when a simulation panics.
+
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::{
+    common_inputs::{Char, Key, MouseButton},
+    input_event::SetTo,
+    simulatable::Simulatable,
+    simulator::Simulate,
+    simulators::virtual_state::{SharedState, Tracked},
+};
+
+#[cfg(test)]
+mod test;
+
+pub use ctrlc;
+
+/// Exit code of a process that is terminated by Ctrl-C.
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// Set a Ctrl-C (`SIGINT`, `SIGTERM` and `SIGHUP` on Unix) handler
+/// that releases every input held in `state` then exits the process.
+///
+/// The simulator is created by `simulator` in the handler thread
+/// because most simulators can't be sent between threads.
+///
+/// This can only be called once per process,
+/// see [`ctrlc::set_handler`] for errors.
+pub fn release_on_interrupt<S, F>(state: SharedState, mut simulator: F) -> Result<(), ctrlc::Error>
+where
+    F: FnMut() -> S + Send + 'static,
+    S: Simulate<SetTo<Key, bool>>
+        + Simulate<SetTo<MouseButton, bool>>
+        + Simulate<SetTo<Char, bool>>,
+{
+    ctrlc::set_handler(move || {
+        let mut simulator = simulator();
+        state.release_all().run_with(&mut simulator);
+        std::process::exit(INTERRUPTED_EXIT_CODE);
+    })
+}
+
+/// Run `f` with a tracked simulator,
+/// every input still held is released if it panics and the panic is resumed.
+pub fn release_on_panic<S, R, F>(simulator: &mut Tracked<S>, f: F) -> R
+where
+    F: FnOnce(&mut Tracked<S>) -> R,
+    S: Simulate<SetTo<Key, bool>>
+        + Simulate<SetTo<MouseButton, bool>>
+        + Simulate<SetTo<Char, bool>>,
+{
+    match panic::catch_unwind(AssertUnwindSafe(|| f(simulator))) {
+        Ok(result) => result,
+        Err(payload) => {
+            simulator.release_all();
+            panic::resume_unwind(payload)
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::input_event::SetTo;
+use crate::prelude::*;
+use crate::simulator::Simulate;
+use crate::{assert_event, simulators::string_event_logger::StringEventLogger as S};
+
+use super::release_on_panic;
+
+/// Logger that panics when Tab is pressed.
+#[derive(Debug, Default)]
+struct TabPanics(S);
+
+impl<I> Simulate<SetTo<I, bool>> for TabPanics
+where
+    I: Clone + Into<crate::simulators::virtual_state::Button>,
+    S: Simulate<SetTo<I, bool>>,
+{
+    fn simulate(&mut self, simulatable: SetTo<I, bool>) {
+        let button = simulatable.input.clone().into();
+        if simulatable.to && button == Key::Tab.into() {
+            panic!("tab is broken");
+        }
+        self.0.simulate(simulatable)
+    }
+}
+
+#[test]
+fn recovery_release_on_panic() {
+    let mut s = TabPanics::default().tracked();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        release_on_panic(&mut s, |s| {
+            (
+                Key::Shift.down(),
+                MouseButton::Left.down(),
+                Key::Tab.click(),
+            )
+                .seq()
+                .run_with(s)
+        })
+    }));
+    assert!(result.is_err());
+    assert!(s.layer.pressed().is_empty());
+    let logger = s.inner.0;
+    assert_event!(logger, 2, MouseButton::Left.up());
+    assert_event!(logger, 3, Key::Shift.up());
+    assert_eq!(logger.data.len(), 4);
+}
+
+#[test]
+fn recovery_no_panic() {
+    let mut s = S::new().tracked();
+    let pressed = release_on_panic(&mut s, |s| {
+        Key::Alt.down().run_with(s);
+        s.layer.pressed().len()
+    });
+    // only released on panic
+    assert_eq!(pressed, 1);
+    assert_eq!(s.inner.data.len(), 1);
+}
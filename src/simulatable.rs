@@ -6,3 +6,15 @@ pub trait Simulatable<Smlt>: Sized {
     /// Simulate this input.
     fn run_with(self, simulator: &mut Smlt);
 }
+
+/// Simulate the value if there is one.
+impl<S, Smlt> Simulatable<Smlt> for Option<S>
+where
+    S: Simulatable<Smlt>,
+{
+    fn run_with(self, simulator: &mut Smlt) {
+        if let Some(s) = self {
+            s.run_with(simulator)
+        }
+    }
+}
@@ -1,8 +1,98 @@
 //! Module for implementing a simulator
 
+use crate::simulators::{
+    balance_checker::{BalanceChecker, Checked},
+    virtual_state::{Tracked, VirtualState},
+};
+
+#[cfg(test)]
+mod test;
+
 /// Trait to implement for a simulator.
 /// It may support many type of input (`E`).
 pub trait Simulate<S> {
     /// Simulate this simulatable
     fn simulate(&mut self, simulatable: S);
 }
+
+impl<S, Smlt> Simulate<S> for &mut Smlt
+where
+    Smlt: Simulate<S>,
+{
+    fn simulate(&mut self, simulatable: S) {
+        (**self).simulate(simulatable)
+    }
+}
+
+/// Middleware around a simulator.
+///
+/// A layer gets every simulatable before the inner simulator does
+/// and decides what to pass on, if anything.
+/// Implement this generically over `E` to support every simulatable
+/// the inner simulator supports.
+///
+/// See [`layers`](crate::layers) for built-in layers.
+pub trait Layer<E, S> {
+    /// Handle `simulatable` on behalf of `inner`.
+    fn handle(&mut self, simulatable: E, inner: &mut S);
+}
+
+/// Simulator wrapped in a [`Layer`].
+/// Created by [`SimulatorExt::layer`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Layered<L, S> {
+    pub layer: L,
+    pub inner: S,
+}
+
+impl<L, S> Layered<L, S> {
+    pub fn new(layer: L, inner: S) -> Layered<L, S> {
+        Layered { layer, inner }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    pub fn into_parts(self) -> (L, S) {
+        (self.layer, self.inner)
+    }
+}
+
+impl<E, L, S> Simulate<E> for Layered<L, S>
+where
+    L: Layer<E, S>,
+{
+    fn simulate(&mut self, simulatable: E) {
+        self.layer.handle(simulatable, &mut self.inner)
+    }
+}
+
+/// Helper simulator trait.
+pub trait SimulatorExt: Sized {
+    /// Wrap self in a layer.
+    /// The last added layer is the first one to get simulatables.
+    fn layer<L>(self, layer: L) -> Layered<L, Self> {
+        Layered::new(layer, self)
+    }
+
+    /// Track the state of inputs passed to self, see [`Tracked`].
+    fn tracked(self) -> Tracked<Self> {
+        self.layer(VirtualState::new())
+    }
+
+    /// Check that inputs passed to self are balanced, see [`Checked`].
+    fn checked(self) -> Checked<Self> {
+        self.layer(BalanceChecker::new())
+    }
+}
+
+impl<S> SimulatorExt for S {}
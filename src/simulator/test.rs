@@ -0,0 +1,72 @@
+use crate::input_event::SetTo;
+use crate::layers::{Log, Remap};
+use crate::prelude::*;
+use crate::{assert_event, simulators::string_event_logger::StringEventLogger as S};
+
+use super::{Layer, Simulate};
+
+/// Drop every key event, pass on the rest.
+struct NoKeys;
+
+impl<S, V> Layer<SetTo<Key, V>, S> for NoKeys {
+    fn handle(&mut self, _simulatable: SetTo<Key, V>, _inner: &mut S) {}
+}
+
+impl<S, V> Layer<SetTo<MouseButton, V>, S> for NoKeys
+where
+    S: Simulate<SetTo<MouseButton, V>>,
+{
+    fn handle(&mut self, simulatable: SetTo<MouseButton, V>, inner: &mut S) {
+        inner.simulate(simulatable)
+    }
+}
+
+#[test]
+fn layer_drops() {
+    let mut s = S::new().layer(NoKeys);
+    (Key::Alt.click(), MouseButton::Left.click())
+        .seq()
+        .run_with(&mut s);
+    let s = s.into_inner();
+    assert_event!(s, 0, MouseButton::Left.down());
+    assert_event!(s, 1, MouseButton::Left.up());
+    assert_eq!(s.data.len(), 2);
+}
+
+#[test]
+fn layer_order() {
+    // the last added layer sees the simulatable first
+    let mut s = S::new()
+        .layer(Remap::new().key(Key::Alt, Key::Shift))
+        .layer(Remap::new().key(Key::Shift, Key::Tab));
+    Key::Alt.down().run_with(&mut s);
+    Key::Shift.down().run_with(&mut s);
+    let (_, inner) = s.into_parts();
+    let s = inner.into_inner();
+    assert_event!(s, 0, Key::Shift.down());
+    assert_event!(s, 1, Key::Tab.down());
+}
+
+#[test]
+fn layer_log_passes_on() {
+    let mut s = S::new().layer(Log::new(Vec::new()));
+    Key::Alt.click().run_with(&mut s);
+    let (log, s) = s.into_parts();
+    assert_eq!(
+        String::from_utf8(log.writer).unwrap(),
+        format!("{:?}\n{:?}\n", Key::Alt.down(), Key::Alt.up())
+    );
+    assert_event!(s, 0, Key::Alt.down());
+    assert_event!(s, 1, Key::Alt.up());
+}
+
+#[test]
+fn layer_tracked_checked() {
+    let mut s = S::new().checked().tracked();
+    Key::Alt.down().run_with(&mut s);
+    assert!(s.layer.is_pressed(Key::Alt));
+    assert!(!s.inner.layer.report().is_ok());
+    s.release_all();
+    assert!(s.inner.layer.report().is_ok());
+    assert_eq!(s.inner.inner.data.len(), 2);
+}
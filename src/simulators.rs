@@ -3,5 +3,10 @@
 #[cfg(feature = "enigo")]
 pub mod enigo;
 
+pub mod balance_checker;
+pub mod dry_run;
+pub mod tee;
+pub mod virtual_state;
+
 #[cfg(any(test, doctest, feature = "test"))]
 pub mod string_event_logger;
@@ -0,0 +1,242 @@
+//! Simulator that finds unbalanced presses and releases.
+//!
+//! A press (`SetTo<_, true>`) should always be followed by a release (`SetTo<_, false>`)
+//! of the same input at some point. [`BalanceChecker`] reports:
+//! - inputs that are still pressed at the end,
+//! - presses of inputs that are already pressed,
+//! - releases of inputs that were never pressed.
+//!
+//! As a [`Layer`] it does the same while passing every input to another simulator,
+//! see [`Checked`].
+//!
+//! ```
+//! use kemuler::prelude::*;
+//! use kemuler::simulators::balance_checker::{BalanceChecker, IssueKind};
+//!
+//! let mut s = BalanceChecker::new();
+//! (
+//!     Key::Control.down(),
+//!     Char('c').down().repeat(2),
+//!     Key::Shift.up(),
+//! )
+//!     .seq()
+//!     .run_with(&mut s);
+//!
+//! let report = s.report();
+//! assert_eq!(report.issues.len(), 4);
+//! assert_eq!(report.issues[0].index, 2);
+//! assert_eq!(report.issues[0].kind, IssueKind::AlreadyPressed);
+//! assert_eq!(report.issues[1].kind, IssueKind::NeverPressed);
+//! assert_eq!(report.issues[2].kind, IssueKind::NeverReleased);
+//! assert_eq!(
+//!     report.to_string(),
+//!     "#2 [set 'c' to true]: pressed while already pressed\n\
+//!      #3 [set Shift to false]: released without being pressed\n\
+//!      #0 [set Control to true]: never released\n\
+//!      #1 [set 'c' to true]: never released\n"
+//! );
+//! ```
+
+use std::fmt;
+
+#[cfg(feature = "spin_sleep")]
+use crate::combinator::SpinSleep;
+use crate::{
+    combinator::Sleep,
+    common_inputs::MousePosition,
+    input_event::*,
+    simulator::{Layer, Layered, Simulate},
+    simulators::virtual_state::Button,
+};
+
+#[cfg(test)]
+mod test;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IssueKind {
+    /// Pressed but not released by the end
+    NeverReleased,
+    /// Pressed while it is already pressed
+    AlreadyPressed,
+    /// Released while it is not pressed
+    NeverPressed,
+}
+
+impl fmt::Display for IssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IssueKind::NeverReleased => write!(f, "never released"),
+            IssueKind::AlreadyPressed => write!(f, "pressed while already pressed"),
+            IssueKind::NeverPressed => write!(f, "released without being pressed"),
+        }
+    }
+}
+
+/// An unbalanced event.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Issue {
+    /// Index of the event, counting every input event
+    pub index: usize,
+    /// `Display` text of the event
+    pub event: String,
+    pub kind: IssueKind,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{} {}: {}", self.index, self.event, self.kind)
+    }
+}
+
+/// Every issue found in a run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Report {
+    /// Issues in the order they are found,
+    /// inputs that are never released are at the end.
+    pub issues: Vec<Issue>,
+}
+
+impl Report {
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for issue in &self.issues {
+            writeln!(f, "{issue}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A simulator that doesn't simulate anything
+/// but checks that presses and releases are balanced.
+///
+/// Implemented simulatables:
+/// - `SetTo   <I, bool>` for any `I` that is a [`Button`]
+/// - `SetTo   <common_inputs::MousePosition, (i32, i32)>`
+/// - `ChangeBy<I, V>`
+/// - `Execute <I>`
+/// - `combinator::Sleep` (doesn't sleep nor count as an event)
+/// - `combinator::SpinSleep` (doesn't sleep nor count as an event)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BalanceChecker {
+    index: usize,
+    /// held input, index and text of its press
+    pressed: Vec<(Button, usize, String)>,
+    issues: Vec<Issue>,
+}
+
+impl BalanceChecker {
+    pub fn new() -> BalanceChecker {
+        BalanceChecker::default()
+    }
+
+    /// Amount of events checked so far.
+    pub fn event_count(&self) -> usize {
+        self.index
+    }
+
+    /// Issues found so far, including inputs that are not released yet.
+    pub fn report(&self) -> Report {
+        let never_released = self.pressed.iter().map(|(_, index, event)| Issue {
+            index: *index,
+            event: event.clone(),
+            kind: IssueKind::NeverReleased,
+        });
+        Report {
+            issues: self.issues.iter().cloned().chain(never_released).collect(),
+        }
+    }
+
+    fn check(&mut self, button: Button, is_down: bool, event: &dyn fmt::Display) {
+        let index = self.index;
+        self.index += 1;
+        let position = self.pressed.iter().position(|(b, ..)| *b == button);
+        let kind = match (position, is_down) {
+            (None, true) => {
+                self.pressed.push((button, index, event.to_string()));
+                return;
+            }
+            (Some(i), false) => {
+                self.pressed.remove(i);
+                return;
+            }
+            (Some(_), true) => IssueKind::AlreadyPressed,
+            (None, false) => IssueKind::NeverPressed,
+        };
+        self.issues.push(Issue {
+            index,
+            event: event.to_string(),
+            kind,
+        });
+    }
+}
+
+impl<I> Simulate<SetTo<I, bool>> for BalanceChecker
+where
+    I: Into<Button> + Clone + fmt::Display,
+{
+    fn simulate(&mut self, simulatable: SetTo<I, bool>) {
+        let button = simulatable.input.clone().into();
+        self.check(button, simulatable.to, &simulatable)
+    }
+}
+
+impl Simulate<SetTo<MousePosition, (i32, i32)>> for BalanceChecker {
+    fn simulate(&mut self, _: SetTo<MousePosition, (i32, i32)>) {
+        self.index += 1;
+    }
+}
+
+impl<I, V> Simulate<ChangeBy<I, V>> for BalanceChecker {
+    fn simulate(&mut self, _: ChangeBy<I, V>) {
+        self.index += 1;
+    }
+}
+
+impl<I> Simulate<Execute<I>> for BalanceChecker {
+    fn simulate(&mut self, _: Execute<I>) {
+        self.index += 1;
+    }
+}
+
+impl Simulate<Sleep> for BalanceChecker {
+    fn simulate(&mut self, _: Sleep) {}
+}
+
+#[cfg(feature = "spin_sleep")]
+impl Simulate<SpinSleep> for BalanceChecker {
+    fn simulate(&mut self, _: SpinSleep) {}
+}
+
+/// Check what passes through, see [`Checked`].
+impl<E, S> Layer<E, S> for BalanceChecker
+where
+    E: Clone,
+    S: Simulate<E>,
+    BalanceChecker: Simulate<E>,
+{
+    fn handle(&mut self, simulatable: E, inner: &mut S) {
+        inner.simulate(simulatable.clone());
+        self.simulate(simulatable);
+    }
+}
+
+/// Simulator with the inputs passed to it checked,
+/// a [`Layered`] simulator with a [`BalanceChecker`] as its layer.
+/// Created by [`SimulatorExt::checked`].
+///
+/// Supports every simulatable that both [`BalanceChecker`] and the inner simulator support.
+///
+/// [`SimulatorExt::checked`]: crate::simulator::SimulatorExt::checked
+pub type Checked<S> = Layered<BalanceChecker, S>;
+
+impl<S> Checked<S> {
+    /// Issues found so far, including inputs that are not released yet.
+    pub fn report(&self) -> Report {
+        self.layer.report()
+    }
+}
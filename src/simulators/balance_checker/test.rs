@@ -0,0 +1,71 @@
+use crate::combinator::Sleep;
+use crate::prelude::*;
+use crate::{assert_event, simulators::string_event_logger::StringEventLogger as S};
+
+use super::{BalanceChecker, Issue, IssueKind};
+
+#[test]
+fn balance_checker_balanced() {
+    let mut s = BalanceChecker::new();
+    (
+        Key::Shift.down(),
+        Char('a').click(),
+        Sleep::from_ms(0),
+        MousePosition.move_by(1, 1),
+        Key::Shift.up(),
+    )
+        .seq()
+        .run_with(&mut s);
+    assert!(s.report().is_ok());
+    // sleeps are not events
+    assert_eq!(s.event_count(), 5);
+}
+
+#[test]
+fn balance_checker_release_without_press() {
+    let mut s = BalanceChecker::new();
+    (MouseButton::Left.up(), Key::Tab.click())
+        .seq()
+        .run_with(&mut s);
+    assert_eq!(
+        s.report().issues,
+        vec![Issue {
+            index: 0,
+            event: MouseButton::Left.up().to_string(),
+            kind: IssueKind::NeverPressed,
+        }]
+    );
+}
+
+#[test]
+fn balance_checker_double_press() {
+    let mut s = BalanceChecker::new();
+    (Char('x').down(), Char('x').down(), Char('x').up())
+        .seq()
+        .run_with(&mut s);
+    // the second press is reported, the first one is released
+    assert_eq!(
+        s.report().issues,
+        vec![Issue {
+            index: 1,
+            event: Char('x').down().to_string(),
+            kind: IssueKind::AlreadyPressed,
+        }]
+    );
+
+    Char('x').up().run_with(&mut s);
+    assert_eq!(s.report().issues[1].kind, IssueKind::NeverPressed);
+}
+
+#[test]
+fn balance_checker_checked() {
+    let mut s = S::new().checked();
+    Key::Alt.down().run_with(&mut s);
+    let report = s.report();
+    assert_eq!(report.issues.len(), 1);
+    assert_eq!(report.issues[0].kind, IssueKind::NeverReleased);
+    Key::Alt.up().run_with(&mut s);
+    assert!(s.report().is_ok());
+    let logger = s.inner;
+    assert_event!(logger, 1, Key::Alt.up());
+}
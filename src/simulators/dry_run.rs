@@ -0,0 +1,202 @@
+//! Simulator that prints what would be simulated.
+//!
+//! [`DryRun`] doesn't simulate anything for real,
+//! it writes a line per event to any [`io::Write`] so a macro can be reviewed
+//! before running it with a real simulator.
+//! Every line starts with the time since the start, sleeps included,
+//! and is indented by the amount of inputs being held.
+//!
+//! ```
+//! use kemuler::prelude::*;
+//! use kemuler::combinator::Sleep;
+//! use kemuler::simulators::dry_run::DryRun;
+//!
+//! let mut s = DryRun::new(Vec::new());
+//! (
+//!     MousePosition.move_to(10, 20),
+//!     Char('c').click().during(Key::Control.down()),
+//!     Sleep::from_ms(100),
+//!     MouseScroll.scroll_by(0, -3),
+//! )
+//!     .seq()
+//!     .run_with(&mut s);
+//!
+//! let plan = String::from_utf8(s.finish().unwrap()).unwrap();
+//! assert_eq!(
+//!     plan,
+//!     "     0.000 ms  move mouse to (10, 20)\n\
+//!      \x20    0.000 ms  press Control\n\
+//!      \x20    0.000 ms    press 'c'\n\
+//!      \x20    0.000 ms    release 'c'\n\
+//!      \x20    0.000 ms  release Control\n\
+//!      \x20    0.000 ms  sleep 100ms\n\
+//!      \x20  100.000 ms  scroll by (0, -3)\n\
+//!      \x20  100.000 ms  end, 6 events\n"
+//! );
+//! ```
+
+use std::{fmt, io, time::Duration};
+
+#[cfg(feature = "spin_sleep")]
+use crate::combinator::SpinSleep;
+use crate::{
+    combinator::Sleep,
+    common_inputs::{MousePosition, MouseScroll, StrSequence},
+    input_event::*,
+    simulator::Simulate,
+};
+
+#[cfg(test)]
+mod test;
+
+/// A simulator that doesn't simulate anything
+/// but writes a readable plan of what it has been given.
+///
+/// Errors from the writer stop the output,
+/// the first one is returned by [`DryRun::finish`].
+///
+/// Implemented simulatables:
+/// - `SetTo   <I, bool>` for any `I` that implements `Display`
+/// - `SetTo   <common_inputs::MousePosition, (i32, i32)>`
+/// - `ChangeBy<common_inputs::MousePosition, (i32, i32)>`
+/// - `ChangeBy<common_inputs::MouseScroll  , (i32, i32)>`
+/// - `Execute <common_inputs::StrSequence>`
+/// - `combinator::Sleep` (doesn't sleep)
+/// - `combinator::SpinSleep` (doesn't sleep)
+#[derive(Debug)]
+pub struct DryRun<W> {
+    writer: W,
+    elapsed: Duration,
+    /// amount of inputs being held
+    depth: usize,
+    event_count: usize,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> DryRun<W> {
+    pub fn new(writer: W) -> DryRun<W> {
+        DryRun {
+            writer,
+            elapsed: Duration::ZERO,
+            depth: 0,
+            event_count: 0,
+            error: None,
+        }
+    }
+
+    /// Total time of every sleep so far.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Amount of events so far, sleeps are not counted.
+    pub fn event_count(&self) -> usize {
+        self.event_count
+    }
+
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+
+    /// Write the last line with the total time and amount of events
+    /// then return the writer, or the first error.
+    pub fn finish(mut self) -> io::Result<W> {
+        let line = format!("end, {} events", self.event_count);
+        self.write_line(&line);
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn write_line(&mut self, text: &dyn fmt::Display) {
+        if self.error.is_some() {
+            return;
+        }
+        let result = writeln!(
+            self.writer,
+            "{:>10.3} ms  {:indent$}{}",
+            self.elapsed.as_secs_f64() * 1000.0,
+            "",
+            text,
+            indent = self.depth * 2,
+        );
+        if let Err(error) = result {
+            self.error = Some(error);
+        }
+    }
+
+    fn event(&mut self, text: &dyn fmt::Display) {
+        self.event_count += 1;
+        self.write_line(text);
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        self.write_line(&format_args!("sleep {duration:?}"));
+        self.elapsed += duration;
+    }
+}
+
+impl DryRun<io::Stdout> {
+    pub fn stdout() -> DryRun<io::Stdout> {
+        DryRun::new(io::stdout())
+    }
+}
+
+impl<I, W> Simulate<SetTo<I, bool>> for DryRun<W>
+where
+    I: fmt::Display,
+    W: io::Write,
+{
+    fn simulate(&mut self, simulatable: SetTo<I, bool>) {
+        let SetTo { input, to } = simulatable;
+        if to {
+            self.event(&format_args!("press {input}"));
+            self.depth += 1;
+        } else {
+            self.depth = self.depth.saturating_sub(1);
+            self.event(&format_args!("release {input}"));
+        }
+    }
+}
+
+impl<W: io::Write> Simulate<SetTo<MousePosition, (i32, i32)>> for DryRun<W> {
+    fn simulate(&mut self, simulatable: SetTo<MousePosition, (i32, i32)>) {
+        let (x, y) = simulatable.to;
+        self.event(&format_args!("move mouse to ({x}, {y})"));
+    }
+}
+
+impl<W: io::Write> Simulate<ChangeBy<MousePosition, (i32, i32)>> for DryRun<W> {
+    fn simulate(&mut self, simulatable: ChangeBy<MousePosition, (i32, i32)>) {
+        let (x, y) = simulatable.by;
+        self.event(&format_args!("move mouse by ({x}, {y})"));
+    }
+}
+
+impl<W: io::Write> Simulate<ChangeBy<MouseScroll, (i32, i32)>> for DryRun<W> {
+    fn simulate(&mut self, simulatable: ChangeBy<MouseScroll, (i32, i32)>) {
+        let (x, y) = simulatable.by;
+        self.event(&format_args!("scroll by ({x}, {y})"));
+    }
+}
+
+impl<'a, W: io::Write> Simulate<Execute<StrSequence<'a>>> for DryRun<W> {
+    fn simulate(&mut self, simulatable: Execute<StrSequence<'a>>) {
+        self.event(&format_args!("type {:?}", simulatable.input.0));
+    }
+}
+
+impl<W: io::Write> Simulate<Sleep> for DryRun<W> {
+    fn simulate(&mut self, simulatable: Sleep) {
+        self.sleep(simulatable.0)
+    }
+}
+
+#[cfg(feature = "spin_sleep")]
+impl<W: io::Write> Simulate<SpinSleep> for DryRun<W> {
+    fn simulate(&mut self, simulatable: SpinSleep) {
+        self.sleep(simulatable.0)
+    }
+}
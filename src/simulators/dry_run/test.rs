@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use crate::combinator::Sleep;
+use crate::prelude::*;
+
+use super::DryRun;
+
+/// Lines of the plan without the time column.
+fn plan_lines(s: DryRun<Vec<u8>>) -> Vec<String> {
+    let plan = String::from_utf8(s.finish().unwrap()).unwrap();
+    plan.lines().map(|line| line[15..].to_string()).collect()
+}
+
+#[test]
+fn dry_run_nested_depth() {
+    let mut s = DryRun::new(Vec::new());
+    Char('c')
+        .click()
+        .during(Key::Shift.down())
+        .during(Key::Control.down())
+        .run_with(&mut s);
+    assert_eq!(
+        plan_lines(s),
+        [
+            "press Control",
+            "  press Shift",
+            "    press 'c'",
+            "    release 'c'",
+            "  release Shift",
+            "release Control",
+            "end, 6 events",
+        ]
+    );
+}
+
+#[test]
+fn dry_run_depth_inside_sequence() {
+    let mut s = DryRun::new(Vec::new());
+    (
+        MouseButton::Left.down(),
+        (MousePosition.move_by(5, 0), Sleep::from_ms(10)).seq(),
+        MouseButton::Left.up(),
+        MouseScroll.scroll_by(0, 1),
+    )
+        .seq()
+        .run_with(&mut s);
+    assert_eq!(s.elapsed(), Duration::from_millis(10));
+    assert_eq!(
+        plan_lines(s),
+        [
+            "press Left",
+            "  move mouse by (5, 0)",
+            "  sleep 10ms",
+            "release Left",
+            "scroll by (0, 1)",
+            "end, 4 events",
+        ]
+    );
+}
+
+#[test]
+fn dry_run_release_without_press() {
+    let mut s = DryRun::new(Vec::new());
+    (Key::Alt.up(), Key::Tab.click()).seq().run_with(&mut s);
+    assert_eq!(
+        plan_lines(s),
+        ["release Alt", "press Tab", "release Tab", "end, 3 events"]
+    );
+}
@@ -1,13 +1,22 @@
 //! Simulate input using `Enigo`.
 
-use crate::{common_inputs, input_event::*, simulator::Simulate};
-use common_inputs::ButtonLike;
+#[cfg(feature = "spin_sleep")]
+use crate::combinator::SpinSleep;
+use crate::{
+    combinator::Sleep,
+    common_inputs, control,
+    input_event::*,
+    query::{Displays, IsPressed, Query, ScreenSize},
+    simulator::Simulate,
+};
+use common_inputs::{ButtonLike, MouseButtonLike};
 use enigo::{KeyboardControllable, MouseControllable};
 
 pub use enigo;
 
 impl ButtonLike for enigo::Key {}
 impl ButtonLike for enigo::MouseButton {}
+impl MouseButtonLike for enigo::MouseButton {}
 
 /// Simulate input using `Enigo`.
 ///
@@ -16,9 +25,19 @@ impl ButtonLike for enigo::MouseButton {}
 /// - `SetTo   <enigo::MouseButton          , bool>`
 /// - `SetTo   <common_inputs::Key          , bool>`
 /// - `SetTo   <common_inputs::MouseButton  , bool>`
+/// - `SetTo   <common_inputs::Char         , bool>`
 /// - `SetTo   <common_inputs::MousePosition, (i32, i32)>`
 /// - `ChangeBy<common_inputs::MousePosition, (i32, i32)>`
 /// - `ChangeBy<common_inputs::MouseScroll  , (i32, i32)>`
+/// - `Execute <common_inputs::StrSequence>`
+/// - `combinator::Sleep`
+/// - `combinator::SpinSleep`
+///
+/// Answered queries:
+/// - `common_inputs::MousePosition`
+/// - `query::ScreenSize`
+/// - `query::Displays` (only the main display)
+/// - `query::IsPressed` (always `None`, track inputs to know)
 #[derive(Debug, Default)]
 pub struct Enigo(pub enigo::Enigo);
 
@@ -57,6 +76,7 @@ fn enigoify_common_key(key: common_inputs::Key) -> enigo::Key {
         common_inputs::Key::Alt => enigo::Key::Alt,
         common_inputs::Key::Shift => enigo::Key::Shift,
         common_inputs::Key::Control => enigo::Key::Control,
+        common_inputs::Key::Meta => enigo::Key::Meta,
         common_inputs::Key::F1 => enigo::Key::F1,
         common_inputs::Key::F2 => enigo::Key::F2,
         common_inputs::Key::F3 => enigo::Key::F3,
@@ -116,6 +136,20 @@ impl Simulate<SetTo<common_inputs::Key, bool>> for Enigo {
     }
 }
 
+impl Simulate<SetTo<common_inputs::Char, bool>> for Enigo {
+    fn simulate(&mut self, simulatable: SetTo<common_inputs::Char, bool>) {
+        let SetTo {
+            input: common_inputs::Char(c),
+            to: is_down,
+        } = simulatable;
+        if is_down {
+            self.0.key_down(enigo::Key::Layout(c))
+        } else {
+            self.0.key_up(enigo::Key::Layout(c))
+        }
+    }
+}
+
 impl Simulate<SetTo<enigo::MouseButton, bool>> for Enigo {
     fn simulate(&mut self, simulatable: SetTo<enigo::MouseButton, bool>) {
         let SetTo {
@@ -167,3 +201,51 @@ impl Simulate<ChangeBy<common_inputs::MouseScroll, (i32, i32)>> for Enigo {
         }
     }
 }
+
+impl<'a> Simulate<Execute<common_inputs::StrSequence<'a>>> for Enigo {
+    fn simulate(&mut self, simulatable: Execute<common_inputs::StrSequence<'a>>) {
+        let Execute {
+            input: common_inputs::StrSequence(s),
+        } = simulatable;
+        self.0.key_sequence(s)
+    }
+}
+
+impl Simulate<Sleep> for Enigo {
+    fn simulate(&mut self, simulatable: Sleep) {
+        control::sleep(simulatable.0)
+    }
+}
+
+#[cfg(feature = "spin_sleep")]
+impl Simulate<SpinSleep> for Enigo {
+    fn simulate(&mut self, simulatable: SpinSleep) {
+        control::spin_sleep(simulatable.0)
+    }
+}
+
+impl Query<common_inputs::MousePosition> for Enigo {
+    fn query(&self, _: common_inputs::MousePosition) -> Option<(i32, i32)> {
+        Some(self.0.mouse_location())
+    }
+}
+
+impl Query<ScreenSize> for Enigo {
+    fn query(&self, _: ScreenSize) -> Option<(u32, u32)> {
+        let (width, height) = self.0.main_display_size();
+        Some((width.try_into().ok()?, height.try_into().ok()?))
+    }
+}
+
+impl Query<Displays> for Enigo {
+    fn query(&self, _: Displays) -> Option<common_inputs::screen::DisplayLayout> {
+        let (width, height) = self.query(ScreenSize)?;
+        Some(common_inputs::screen::DisplayLayout::single(width, height))
+    }
+}
+
+impl<B: Into<crate::simulators::virtual_state::Button>> Query<IsPressed<B>> for Enigo {
+    fn query(&self, _: IsPressed<B>) -> Option<bool> {
+        None
+    }
+}
@@ -2,7 +2,9 @@
 
 use std::fmt;
 
-use crate::{common_inputs, input_event::*, simulator::Simulate};
+#[cfg(feature = "spin_sleep")]
+use crate::combinator::SpinSleep;
+use crate::{combinator::Sleep, common_inputs, control, input_event::*, simulator::Simulate};
 
 #[macro_export]
 macro_rules! assert_event {
@@ -26,9 +28,13 @@ macro_rules! assert_event {
 /// - `SetTo   <enigo::MouseButton          , bool>`
 /// - `SetTo   <common_inputs::Key          , bool>`
 /// - `SetTo   <common_inputs::MouseButton  , bool>`
+/// - `SetTo   <common_inputs::Char         , bool>`
 /// - `SetTo   <common_inputs::MousePosition, (i32, i32)>`
 /// - `ChangeBy<common_inputs::MousePosition, (i32, i32)>`
 /// - `ChangeBy<common_inputs::MouseScroll  , (i32, i32)>`
+/// - `Execute <common_inputs::StrSequence>`
+/// - `combinator::Sleep` (sleeps without logging)
+/// - `combinator::SpinSleep` (sleeps without logging)
 #[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Hash)]
 pub struct StringEventLogger {
     pub data: Vec<String>,
@@ -71,3 +77,22 @@ impl_logger! {
     ChangeBy<common_inputs::MousePosition, (i32, i32)>
     ChangeBy<common_inputs::MouseScroll, (i32, i32)>
 }
+
+impl<'a> Simulate<Execute<common_inputs::StrSequence<'a>>> for StringEventLogger {
+    fn simulate(&mut self, simulatable: Execute<common_inputs::StrSequence<'a>>) {
+        self.push(&simulatable)
+    }
+}
+
+impl Simulate<Sleep> for StringEventLogger {
+    fn simulate(&mut self, simulatable: Sleep) {
+        control::sleep(simulatable.0)
+    }
+}
+
+#[cfg(feature = "spin_sleep")]
+impl Simulate<SpinSleep> for StringEventLogger {
+    fn simulate(&mut self, simulatable: SpinSleep) {
+        control::spin_sleep(simulatable.0)
+    }
+}
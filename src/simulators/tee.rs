@@ -0,0 +1,99 @@
+//! Simulators that send every simulatable to multiple simulators.
+//!
+//! ```
+//! # use kemuler::simulators::string_event_logger::StringEventLogger as Simulator;
+//! use kemuler::prelude::*;
+//! use kemuler::simulators::{tee::{FanOut, Tee}, virtual_state::VirtualState};
+//!
+//! let mut real = Simulator::new(); // use your preferred simulator
+//! let mut recorder = Simulator::new();
+//!
+//! Key::Alt.click().run_with(&mut Tee(&mut real, &mut recorder));
+//! assert_eq!(real.data, recorder.data);
+//!
+//! let mut s = FanOut((Simulator::new(), VirtualState::new(), Simulator::new()));
+//! Key::Alt.down().run_with(&mut s);
+//! assert!(s.0 .1.is_pressed(Key::Alt));
+//! ```
+
+use crate::simulator::Simulate;
+
+#[cfg(test)]
+mod test;
+
+/// Send every simulatable to `A` and then to `B`.
+///
+/// Supports every simulatable that both `A` and `B` support.
+/// Use `&mut` simulators to keep the ownership.
+///
+/// A [`Sleep`](crate::combinator::Sleep) is passed to both too,
+/// so it's slept twice if both simulators sleep for real.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Tee<A, B>(pub A, pub B);
+
+impl<E, A, B> Simulate<E> for Tee<A, B>
+where
+    E: Clone,
+    A: Simulate<E>,
+    B: Simulate<E>,
+{
+    fn simulate(&mut self, simulatable: E) {
+        self.0.simulate(simulatable.clone());
+        self.1.simulate(simulatable);
+    }
+}
+
+/// Send every simulatable to many simulators in order.
+///
+/// Simulators can be in a tuple of size 1 <= size <= 12,
+/// or in a `Vec` if they're the same type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct FanOut<T>(pub T);
+
+impl<E, S> Simulate<E> for FanOut<Vec<S>>
+where
+    E: Clone,
+    S: Simulate<E>,
+{
+    fn simulate(&mut self, simulatable: E) {
+        for simulator in &mut self.0 {
+            simulator.simulate(simulatable.clone());
+        }
+    }
+}
+
+/// implement fan out for each tuple size
+macro_rules! fan_out_impl {
+    ($( ($($n:tt => $g:ident),*) )*) => {
+        $(
+            impl<E, $($g,)*> Simulate<E> for FanOut<($($g,)*)>
+            where
+                E: Clone,
+                $(
+                    $g: Simulate<E>,
+                )*
+            {
+                fn simulate(&mut self, simulatable: E) {
+                    $(
+                        self.0.$n.simulate(simulatable.clone());
+                    )*
+                }
+            }
+        )*
+    };
+}
+
+fan_out_impl! {
+    (0 => S0)
+    (0 => S0, 1 => S1)
+    (0 => S0, 1 => S1, 2 => S2)
+    (0 => S0, 1 => S1, 2 => S2, 3 => S3)
+    (0 => S0, 1 => S1, 2 => S2, 3 => S3, 4 => S4)
+    (0 => S0, 1 => S1, 2 => S2, 3 => S3, 4 => S4, 5 => S5)
+    (0 => S0, 1 => S1, 2 => S2, 3 => S3, 4 => S4, 5 => S5, 6 => S6)
+    (0 => S0, 1 => S1, 2 => S2, 3 => S3, 4 => S4, 5 => S5, 6 => S6, 7 => S7)
+    (0 => S0, 1 => S1, 2 => S2, 3 => S3, 4 => S4, 5 => S5, 6 => S6, 7 => S7, 8 => S8)
+    (0 => S0, 1 => S1, 2 => S2, 3 => S3, 4 => S4, 5 => S5, 6 => S6, 7 => S7, 8 => S8, 9 => S9)
+    (0 => S0, 1 => S1, 2 => S2, 3 => S3, 4 => S4, 5 => S5, 6 => S6, 7 => S7, 8 => S8, 9 => S9, 10 => S10)
+    (0 => S0, 1 => S1, 2 => S2, 3 => S3, 4 => S4, 5 => S5, 6 => S6, 7 => S7, 8 => S8, 9 => S9, 10 => S10, 11 => S11)
+}
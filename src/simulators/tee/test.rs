@@ -0,0 +1,83 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::input_event::SetTo;
+use crate::prelude::*;
+use crate::simulator::Simulate;
+use crate::{assert_event, simulators::string_event_logger::StringEventLogger as S};
+
+use super::{FanOut, Tee};
+
+/// Simulator that writes its name and every key event to a shared log.
+#[derive(Debug, Clone)]
+struct Named(&'static str, Rc<RefCell<Vec<String>>>);
+
+impl Simulate<SetTo<Key, bool>> for Named {
+    fn simulate(&mut self, simulatable: SetTo<Key, bool>) {
+        self.1
+            .borrow_mut()
+            .push(format!("{} {simulatable}", self.0));
+    }
+}
+
+fn named(names: [&'static str; 3]) -> (Rc<RefCell<Vec<String>>>, [Named; 3]) {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let simulators = names.map(|name| Named(name, log.clone()));
+    (log, simulators)
+}
+
+#[test]
+fn tee_both() {
+    let mut a = S::new();
+    let mut b = S::new();
+    Key::Alt.click().run_with(&mut Tee(&mut a, &mut b));
+    assert_event!(a, 0, Key::Alt.down());
+    assert_event!(a, 1, Key::Alt.up());
+    assert_eq!(a.data, b.data);
+}
+
+#[test]
+fn tee_order() {
+    let (log, [a, b, _]) = named(["a", "b", "c"]);
+    Key::Alt.click().run_with(&mut Tee(a, b));
+    let down = Key::Alt.down();
+    let up = Key::Alt.up();
+    assert_eq!(
+        *log.borrow(),
+        [
+            format!("a {down}"),
+            format!("b {down}"),
+            format!("a {up}"),
+            format!("b {up}"),
+        ]
+    );
+}
+
+#[test]
+fn fan_out_tuple_order() {
+    let (log, [a, b, c]) = named(["a", "b", "c"]);
+    Key::Alt.down().run_with(&mut FanOut((a, b, c)));
+    let down = Key::Alt.down();
+    assert_eq!(
+        *log.borrow(),
+        [
+            format!("a {down}"),
+            format!("b {down}"),
+            format!("c {down}"),
+        ]
+    );
+}
+
+#[test]
+fn fan_out_vec_order() {
+    let (log, simulators) = named(["c", "a", "b"]);
+    Key::Alt.down().run_with(&mut FanOut(simulators.to_vec()));
+    let down = Key::Alt.down();
+    assert_eq!(
+        *log.borrow(),
+        [
+            format!("c {down}"),
+            format!("a {down}"),
+            format!("b {down}"),
+        ]
+    );
+}
@@ -0,0 +1,374 @@
+//! Simulator that keeps track of input state.
+//!
+//! [`VirtualState`] doesn't simulate anything for real,
+//! it only remembers what is being held and where the mouse cursor is.
+//! As a [`Layer`] it does the same while passing every input to another simulator,
+//! see [`Tracked`].
+//!
+//! ```
+//! # use kemuler::simulators::string_event_logger::StringEventLogger as Simulator;
+//! use kemuler::prelude::*;
+//! use kemuler::simulators::virtual_state::VirtualState;
+//!
+//! let mut s = VirtualState::new();
+//! (
+//!     Key::Shift.down(),
+//!     MouseButton::Left.click(),
+//!     MousePosition.move_to(100, 100),
+//!     MousePosition.move_by(-10, 5),
+//!     MouseScroll.scroll_by(0, 3),
+//! )
+//!     .seq()
+//!     .run_with(&mut s);
+//!
+//! assert!(s.is_pressed(Key::Shift));
+//! assert!(!s.is_pressed(MouseButton::Left));
+//! assert_eq!(s.mouse_position(), (90, 105));
+//! assert_eq!(s.scroll(), (0, 3));
+//!
+//! // wrap any simulator to track what it has been given
+//! let mut s = Simulator::new().tracked();
+//! Char('a').down().run_with(&mut s);
+//! assert!(s.layer.is_pressed(Char('a')));
+//! assert_eq!(s.inner.data.len(), 1);
+//!
+//! // release everything that is still held
+//! s.release_all();
+//! assert!(s.layer.pressed().is_empty());
+//! assert_eq!(s.inner.data.len(), 2);
+//! ```
+
+use std::{
+    fmt,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+#[cfg(feature = "spin_sleep")]
+use crate::combinator::SpinSleep;
+use crate::{
+    combinator::Sleep,
+    common_inputs::{ButtonLike, Char, Key, MouseButton, MousePosition, MouseScroll, StrSequence},
+    estimate::{Estimate, Estimation},
+    input_event::*,
+    simulatable::Simulatable,
+    simulator::{Layer, Layered, Simulate},
+};
+
+#[cfg(test)]
+mod test;
+
+/// Any input that can be held down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Button {
+    Key(Key),
+    MouseButton(MouseButton),
+    Char(Char),
+}
+
+impl From<Key> for Button {
+    fn from(key: Key) -> Self {
+        Button::Key(key)
+    }
+}
+
+impl From<MouseButton> for Button {
+    fn from(button: MouseButton) -> Self {
+        Button::MouseButton(button)
+    }
+}
+
+impl From<Char> for Button {
+    fn from(c: Char) -> Self {
+        Button::Char(c)
+    }
+}
+
+impl fmt::Display for Button {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Button::Key(key) => write!(f, "{key}"),
+            Button::MouseButton(button) => write!(f, "{button}"),
+            Button::Char(c) => write!(f, "{c}"),
+        }
+    }
+}
+
+/// A simulator that doesn't simulate anything
+/// but remembers the state of inputs it has been given.
+///
+/// Implemented simulatables:
+/// - `SetTo   <common_inputs::Key          , bool>`
+/// - `SetTo   <common_inputs::MouseButton  , bool>`
+/// - `SetTo   <common_inputs::Char         , bool>`
+/// - `SetTo   <common_inputs::MousePosition, (i32, i32)>`
+/// - `ChangeBy<common_inputs::MousePosition, (i32, i32)>`
+/// - `ChangeBy<common_inputs::MouseScroll  , (i32, i32)>`
+/// - `Execute <common_inputs::StrSequence>` (nothing is tracked)
+/// - `combinator::Sleep` (doesn't sleep)
+/// - `combinator::SpinSleep` (doesn't sleep)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VirtualState {
+    pressed: Vec<Button>,
+    mouse_position: (i32, i32),
+    scroll: (i32, i32),
+}
+
+impl VirtualState {
+    pub fn new() -> VirtualState {
+        VirtualState::default()
+    }
+
+    /// Start with the mouse cursor at `(x, y)`.
+    pub fn with_mouse_position(x: i32, y: i32) -> VirtualState {
+        VirtualState {
+            mouse_position: (x, y),
+            ..VirtualState::default()
+        }
+    }
+
+    /// Every input that is being held, in the order they were pressed.
+    pub fn pressed(&self) -> &[Button] {
+        &self.pressed
+    }
+
+    pub fn is_pressed(&self, button: impl Into<Button>) -> bool {
+        self.pressed.contains(&button.into())
+    }
+
+    /// Absolute mouse position.
+    /// This is only correct if the cursor has been moved by `SetTo` at least once
+    /// or this state was created with the correct position.
+    pub fn mouse_position(&self) -> (i32, i32) {
+        self.mouse_position
+    }
+
+    /// Total amount that has been scrolled, saturating at the bounds of `i32`.
+    pub fn scroll(&self) -> (i32, i32) {
+        self.scroll
+    }
+
+    /// Release every input that is being held, in reverse order of pressing.
+    /// Run the result with any simulator to bring it back to a neutral state.
+    ///
+    /// This doesn't change this state,
+    /// run the result with this state too if that's needed.
+    pub fn release_all(&self) -> ReleaseAll {
+        ReleaseAll {
+            pressed: self.pressed.clone(),
+        }
+    }
+
+    fn set_pressed(&mut self, button: Button, is_down: bool) {
+        let position = self.pressed.iter().position(|b| *b == button);
+        match (position, is_down) {
+            (None, true) => self.pressed.push(button),
+            (Some(i), false) => {
+                self.pressed.remove(i);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Simulate<SetTo<Key, bool>> for VirtualState {
+    fn simulate(&mut self, simulatable: SetTo<Key, bool>) {
+        self.set_pressed(simulatable.input.into(), simulatable.to)
+    }
+}
+
+impl Simulate<SetTo<MouseButton, bool>> for VirtualState {
+    fn simulate(&mut self, simulatable: SetTo<MouseButton, bool>) {
+        self.set_pressed(simulatable.input.into(), simulatable.to)
+    }
+}
+
+impl Simulate<SetTo<Char, bool>> for VirtualState {
+    fn simulate(&mut self, simulatable: SetTo<Char, bool>) {
+        self.set_pressed(simulatable.input.into(), simulatable.to)
+    }
+}
+
+impl Simulate<SetTo<MousePosition, (i32, i32)>> for VirtualState {
+    fn simulate(&mut self, simulatable: SetTo<MousePosition, (i32, i32)>) {
+        self.mouse_position = simulatable.to;
+    }
+}
+
+impl Simulate<ChangeBy<MousePosition, (i32, i32)>> for VirtualState {
+    fn simulate(&mut self, simulatable: ChangeBy<MousePosition, (i32, i32)>) {
+        let (x, y) = self.mouse_position;
+        let (by_x, by_y) = simulatable.by;
+        self.mouse_position = (x.saturating_add(by_x), y.saturating_add(by_y));
+    }
+}
+
+impl Simulate<ChangeBy<MouseScroll, (i32, i32)>> for VirtualState {
+    fn simulate(&mut self, simulatable: ChangeBy<MouseScroll, (i32, i32)>) {
+        let (x, y) = self.scroll;
+        let (by_x, by_y) = simulatable.by;
+        self.scroll = (x.saturating_add(by_x), y.saturating_add(by_y));
+    }
+}
+
+impl<'a> Simulate<Execute<StrSequence<'a>>> for VirtualState {
+    fn simulate(&mut self, _: Execute<StrSequence<'a>>) {}
+}
+
+impl Simulate<Sleep> for VirtualState {
+    fn simulate(&mut self, _: Sleep) {}
+}
+
+#[cfg(feature = "spin_sleep")]
+impl Simulate<SpinSleep> for VirtualState {
+    fn simulate(&mut self, _: SpinSleep) {}
+}
+
+/// Track what passes through, see [`Tracked`].
+impl<E, S> Layer<E, S> for VirtualState
+where
+    E: Clone,
+    S: Simulate<E>,
+    VirtualState: Simulate<E>,
+{
+    fn handle(&mut self, simulatable: E, inner: &mut S) {
+        inner.simulate(simulatable.clone());
+        self.simulate(simulatable);
+    }
+}
+
+/// Release inputs, in reverse order.
+/// Created by [`VirtualState::release_all`].
+///
+/// Every input is released by simulating the [`Invert`] of its press.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ReleaseAll {
+    /// Inputs in the order they were pressed
+    pub pressed: Vec<Button>,
+}
+
+impl<Smlt> Simulatable<Smlt> for ReleaseAll
+where
+    Smlt: Simulate<SetTo<Key, bool>>
+        + Simulate<SetTo<MouseButton, bool>>
+        + Simulate<SetTo<Char, bool>>,
+{
+    fn run_with(self, simulator: &mut Smlt) {
+        for button in self.pressed.into_iter().rev() {
+            match button {
+                Button::Key(key) => key.down().invert().run_with(simulator),
+                Button::MouseButton(button) => button.down().invert().run_with(simulator),
+                Button::Char(c) => c.down().invert().run_with(simulator),
+            }
+        }
+    }
+}
+
+impl Estimate for ReleaseAll {
+    fn estimate(&self) -> Estimation {
+        Estimation::events(self.pressed.len())
+    }
+}
+
+impl fmt::Display for ReleaseAll {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[release all (")?;
+        for (i, button) in self.pressed.iter().rev().enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{button}")?;
+        }
+        write!(f, ")]")
+    }
+}
+
+/// [`VirtualState`] that can be shared between threads.
+///
+/// Cloning this shares the same state.
+/// It's useful when the state has to be read while it's being simulated,
+/// for example from a signal handler, see [`recovery`](crate::recovery).
+#[derive(Debug, Clone, Default)]
+pub struct SharedState(Arc<Mutex<VirtualState>>);
+
+impl SharedState {
+    pub fn new(state: VirtualState) -> SharedState {
+        SharedState(Arc::new(Mutex::new(state)))
+    }
+
+    /// Lock the state for reading or modifying.
+    /// A poisoned state is still returned since tracking can't leave it broken.
+    pub fn lock(&self) -> MutexGuard<'_, VirtualState> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Copy of the current state.
+    pub fn snapshot(&self) -> VirtualState {
+        self.lock().clone()
+    }
+
+    /// See [`VirtualState::release_all`].
+    pub fn release_all(&self) -> ReleaseAll {
+        self.lock().release_all()
+    }
+}
+
+impl<E> Simulate<E> for SharedState
+where
+    VirtualState: Simulate<E>,
+{
+    fn simulate(&mut self, simulatable: E) {
+        self.lock().simulate(simulatable)
+    }
+}
+
+/// Track what passes through, see [`Tracked`].
+impl<E, S> Layer<E, S> for SharedState
+where
+    E: Clone,
+    S: Simulate<E>,
+    VirtualState: Simulate<E>,
+{
+    fn handle(&mut self, simulatable: E, inner: &mut S) {
+        inner.simulate(simulatable.clone());
+        self.simulate(simulatable);
+    }
+}
+
+/// Simulator with the state of inputs passed to it tracked,
+/// a [`Layered`] simulator with the state as its layer.
+///
+/// The state is usually a [`VirtualState`],
+/// or a [`SharedState`] if it needs to be read from another thread.
+/// Created by [`SimulatorExt::tracked`], or [`SimulatorExt::layer`] with the state.
+///
+/// Supports every simulatable that both the state and the inner simulator support.
+///
+/// [`SimulatorExt::tracked`]: crate::simulator::SimulatorExt::tracked
+/// [`SimulatorExt::layer`]: crate::simulator::SimulatorExt::layer
+pub type Tracked<S, V = VirtualState> = Layered<V, S>;
+
+impl<S> Tracked<S>
+where
+    S: Simulate<SetTo<Key, bool>>
+        + Simulate<SetTo<MouseButton, bool>>
+        + Simulate<SetTo<Char, bool>>,
+{
+    /// Release every input that is being held by the inner simulator.
+    pub fn release_all(&mut self) {
+        self.layer.release_all().run_with(self);
+    }
+}
+
+impl<S> Tracked<S, SharedState>
+where
+    S: Simulate<SetTo<Key, bool>>
+        + Simulate<SetTo<MouseButton, bool>>
+        + Simulate<SetTo<Char, bool>>,
+{
+    /// Release every input that is being held by the inner simulator.
+    pub fn release_all(&mut self) {
+        self.layer.release_all().run_with(self);
+    }
+}
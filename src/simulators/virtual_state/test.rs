@@ -0,0 +1,83 @@
+use std::thread;
+
+use crate::prelude::*;
+use crate::{assert_event, simulators::string_event_logger::StringEventLogger as S};
+
+use super::{Button, SharedState, VirtualState};
+
+#[test]
+fn virtual_state_press_release() {
+    let mut s = VirtualState::new();
+    (
+        Key::Shift.down(),
+        Char('a').down(),
+        MouseButton::Left.down(),
+        // pressing again doesn't add it twice
+        Key::Shift.down(),
+        Char('a').up(),
+        // releasing what is not pressed does nothing
+        Key::Alt.up(),
+    )
+        .seq()
+        .run_with(&mut s);
+    assert_eq!(
+        s.pressed(),
+        [
+            Button::Key(Key::Shift),
+            Button::MouseButton(MouseButton::Left)
+        ]
+    );
+    assert!(!s.is_pressed(Char('a')));
+}
+
+#[test]
+fn virtual_state_change_by_saturates() {
+    let mut s = VirtualState::with_mouse_position(i32::MAX - 1, i32::MIN + 1);
+    MousePosition.move_by(10, -10).run_with(&mut s);
+    assert_eq!(s.mouse_position(), (i32::MAX, i32::MIN));
+    MousePosition.move_by(-5, 5).run_with(&mut s);
+    assert_eq!(s.mouse_position(), (i32::MAX - 5, i32::MIN + 5));
+
+    (
+        MouseScroll.scroll_by(i32::MAX, i32::MIN),
+        MouseScroll.scroll_by(1, -1),
+    )
+        .seq()
+        .run_with(&mut s);
+    assert_eq!(s.scroll(), (i32::MAX, i32::MIN));
+}
+
+#[test]
+fn virtual_state_release_all() {
+    let mut s = S::new().tracked();
+    (
+        Key::Control.down(),
+        Char('c').down(),
+        MouseButton::Right.down(),
+    )
+        .seq()
+        .run_with(&mut s);
+    assert_eq!(
+        s.layer.release_all().to_string(),
+        "[release all (Right, 'c', Control)]"
+    );
+    s.release_all();
+    assert!(s.layer.pressed().is_empty());
+    // released in reverse order of pressing
+    let logger = s.inner;
+    assert_event!(logger, 3, MouseButton::Right.up());
+    assert_event!(logger, 4, Char('c').up());
+    assert_event!(logger, 5, Key::Control.up());
+    assert_eq!(logger.data.len(), 6);
+}
+
+#[test]
+fn virtual_state_shared() {
+    let state = SharedState::default();
+    let mut s = S::new().layer(state.clone());
+    Key::Alt.down().run_with(&mut s);
+    let reader = thread::spawn(move || state.snapshot());
+    assert!(reader.join().unwrap().is_pressed(Key::Alt));
+    s.release_all();
+    assert!(s.layer.lock().pressed().is_empty());
+}
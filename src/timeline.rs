@@ -0,0 +1,352 @@
+//! Module for flattening simulatables into a list of timed events
+//!
+//! A [`Timeline`] is what a simulatable does, in order,
+//! with every sleep folded into the time offset of the events after it.
+//! It can be inspected, compared or simulated later by any simulator
+//! that supports every [`Event`].
+//!
+//! ```
+//! # use kemuler::simulators::string_event_logger::StringEventLogger as Simulator;
+//! use std::time::Duration;
+//! use kemuler::prelude::*;
+//! use kemuler::timeline::{Event, Timeline};
+//!
+//! let timeline = Timeline::compile(
+//!     (
+//!         MouseButton::Left.click(),
+//!         Key::Space.click().sleep_ms(10),
+//!     )
+//!         .seq()
+//!         .repeat(2),
+//! );
+//!
+//! assert_eq!(timeline.len(), 8);
+//! assert_eq!(timeline.duration, Duration::from_millis(20));
+//! assert_eq!(timeline.events[4].offset, Duration::from_millis(10));
+//! assert_eq!(timeline.events[4].event, Event::from(MouseButton::Left.down()));
+//!
+//! let mut s = Simulator::new();
+//! timeline.run_with(&mut s);
+//! assert_eq!(s.data.len(), 8);
+//! ```
+
+use std::{fmt, time::Duration};
+
+#[cfg(feature = "spin_sleep")]
+use crate::combinator::SpinSleep;
+use crate::{
+    combinator::Sleep,
+    common_inputs::{Char, Key, MouseButton, MousePosition, MouseScroll, StrSequence},
+    estimate::{Estimate, Estimation},
+    input_event::*,
+    layers::TimeScale,
+    simulatable::Simulatable,
+    simulator::Simulate,
+};
+
+pub mod optimize;
+
+#[cfg(test)]
+mod test;
+
+/// Simulator that supports every [`Event`].
+///
+/// This is implemented for every simulator that does.
+pub trait SimulateEvent:
+    Simulate<SetTo<Key, bool>>
+    + Simulate<SetTo<MouseButton, bool>>
+    + Simulate<SetTo<Char, bool>>
+    + Simulate<SetTo<MousePosition, (i32, i32)>>
+    + Simulate<ChangeBy<MousePosition, (i32, i32)>>
+    + Simulate<ChangeBy<MouseScroll, (i32, i32)>>
+    + for<'a> Simulate<Execute<StrSequence<'a>>>
+{
+}
+
+impl<S> SimulateEvent for S where
+    S: Simulate<SetTo<Key, bool>>
+        + Simulate<SetTo<MouseButton, bool>>
+        + Simulate<SetTo<Char, bool>>
+        + Simulate<SetTo<MousePosition, (i32, i32)>>
+        + Simulate<ChangeBy<MousePosition, (i32, i32)>>
+        + Simulate<ChangeBy<MouseScroll, (i32, i32)>>
+        + for<'a> Simulate<Execute<StrSequence<'a>>>
+{
+}
+
+/// Any input event of `common_inputs`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Event {
+    Key(SetTo<Key, bool>),
+    MouseButton(SetTo<MouseButton, bool>),
+    Char(SetTo<Char, bool>),
+    MoveTo(SetTo<MousePosition, (i32, i32)>),
+    MoveBy(ChangeBy<MousePosition, (i32, i32)>),
+    Scroll(ChangeBy<MouseScroll, (i32, i32)>),
+    /// `Execute<StrSequence>` with an owned string
+    Type(String),
+}
+
+/// implement `From` an input event for `Event`
+macro_rules! event_from {
+    ($($variant:ident($ty:ty),)*) => {
+        $(
+            impl From<$ty> for Event {
+                fn from(event: $ty) -> Self {
+                    Event::$variant(event)
+                }
+            }
+        )*
+    };
+}
+
+event_from! {
+    Key(SetTo<Key, bool>),
+    MouseButton(SetTo<MouseButton, bool>),
+    Char(SetTo<Char, bool>),
+    MoveTo(SetTo<MousePosition, (i32, i32)>),
+    MoveBy(ChangeBy<MousePosition, (i32, i32)>),
+    Scroll(ChangeBy<MouseScroll, (i32, i32)>),
+}
+
+impl<'a> From<Execute<StrSequence<'a>>> for Event {
+    fn from(event: Execute<StrSequence<'a>>) -> Self {
+        Event::Type(event.input.0.to_owned())
+    }
+}
+
+impl<Smlt> Simulatable<Smlt> for Event
+where
+    Smlt: SimulateEvent,
+{
+    fn run_with(self, simulator: &mut Smlt) {
+        match self {
+            Event::Key(e) => e.run_with(simulator),
+            Event::MouseButton(e) => e.run_with(simulator),
+            Event::Char(e) => e.run_with(simulator),
+            Event::MoveTo(e) => e.run_with(simulator),
+            Event::MoveBy(e) => e.run_with(simulator),
+            Event::Scroll(e) => e.run_with(simulator),
+            Event::Type(s) => StrSequence(&s).execute().run_with(simulator),
+        }
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Key(e) => write!(f, "{e}"),
+            Event::MouseButton(e) => write!(f, "{e}"),
+            Event::Char(e) => write!(f, "{e}"),
+            Event::MoveTo(e) => write!(f, "[set {} to {:?}]", e.input, e.to),
+            Event::MoveBy(e) => write!(f, "[change {} by {:?}]", e.input, e.by),
+            Event::Scroll(e) => write!(f, "[change {} by {:?}]", e.input, e.by),
+            Event::Type(s) => write!(f, "[type {s:?}]"),
+        }
+    }
+}
+
+/// An event and when it happens, counting from the start of the timeline.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Timed {
+    pub offset: Duration,
+    pub event: Event,
+}
+
+/// Flat list of timed events.
+///
+/// Offsets never decrease and are never past `duration`.
+/// Simulating a timeline sleeps between events as needed,
+/// and until `duration` after the last event.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Timeline {
+    pub events: Vec<Timed>,
+    /// Total duration, including sleeps after the last event
+    pub duration: Duration,
+}
+
+impl Timeline {
+    pub fn new() -> Timeline {
+        Timeline::default()
+    }
+
+    /// Flatten a simulatable without simulating it for real.
+    pub fn compile(simulatable: impl Simulatable<Recorder>) -> Timeline {
+        let mut recorder = Recorder::new();
+        simulatable.run_with(&mut recorder);
+        recorder.into_timeline()
+    }
+
+    /// Add an event at the end of the timeline.
+    pub fn push(&mut self, event: impl Into<Event>) {
+        self.events.push(Timed {
+            offset: self.duration,
+            event: event.into(),
+        });
+    }
+
+    /// Extend the timeline by `duration`.
+    pub fn wait(&mut self, duration: Duration) {
+        self.duration += duration;
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Timed> {
+        self.events.iter()
+    }
+
+    /// Scale the time between events, see [`TimeScale`].
+    /// Events at the same time are also kept `min` apart.
+    pub fn scaled(self, scale: &TimeScale) -> Timeline {
+        let mut scaled = Timeline::new();
+        let mut previous = None;
+        for Timed { offset, event } in self.events {
+            match previous {
+                Some(previous) => scaled.wait(scale.gap(offset - previous)),
+                None => scaled.wait(scale.scale(offset)),
+            }
+            previous = Some(offset);
+            scaled.push(event);
+        }
+        let last = previous.unwrap_or(Duration::ZERO);
+        if self.duration > last {
+            scaled.wait(scale.scale(self.duration - last));
+        }
+        scaled
+    }
+}
+
+impl<'a> IntoIterator for &'a Timeline {
+    type Item = &'a Timed;
+    type IntoIter = std::slice::Iter<'a, Timed>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.events.iter()
+    }
+}
+
+impl IntoIterator for Timeline {
+    type Item = Timed;
+    type IntoIter = std::vec::IntoIter<Timed>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.events.into_iter()
+    }
+}
+
+impl<Smlt> Simulatable<Smlt> for Timeline
+where
+    Smlt: SimulateEvent + Simulate<Sleep>,
+{
+    fn run_with(self, simulator: &mut Smlt) {
+        let mut now = Duration::ZERO;
+        for Timed { offset, event } in self.events {
+            if offset > now {
+                Sleep(offset - now).run_with(simulator);
+                now = offset;
+            }
+            event.run_with(simulator);
+        }
+        if self.duration > now {
+            Sleep(self.duration - now).run_with(simulator);
+        }
+    }
+}
+
+impl Estimate for Timeline {
+    fn estimate(&self) -> Estimation {
+        Estimation {
+            duration: self.duration,
+            events: self.events.len(),
+        }
+    }
+}
+
+impl fmt::Display for Timeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for Timed { offset, event } in &self.events {
+            writeln!(f, "{:>10.3} ms  {event}", offset.as_secs_f64() * 1000.0)?;
+        }
+        writeln!(f, "{:>10.3} ms  end", self.duration.as_secs_f64() * 1000.0)
+    }
+}
+
+/// A simulator that doesn't simulate anything
+/// but records what it has been given into a [`Timeline`].
+///
+/// Sleeps don't sleep, they only move the time forward.
+///
+/// Implemented simulatables:
+/// - every [`Event`]
+/// - `combinator::Sleep`
+/// - `combinator::SpinSleep`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Recorder {
+    timeline: Timeline,
+}
+
+impl Recorder {
+    pub fn new() -> Recorder {
+        Recorder::default()
+    }
+
+    pub fn timeline(&self) -> &Timeline {
+        &self.timeline
+    }
+
+    pub fn into_timeline(self) -> Timeline {
+        self.timeline
+    }
+}
+
+/// implement recorder for each event
+macro_rules! impl_recorder {
+    ($($ty:ty)*) => {
+        $(
+            impl Simulate<$ty> for Recorder {
+                fn simulate(&mut self, simulatable: $ty) {
+                    self.timeline.push(simulatable)
+                }
+            }
+        )*
+    };
+}
+
+impl_recorder! {
+    SetTo<Key, bool>
+    SetTo<MouseButton, bool>
+    SetTo<Char, bool>
+    SetTo<MousePosition, (i32, i32)>
+    ChangeBy<MousePosition, (i32, i32)>
+    ChangeBy<MouseScroll, (i32, i32)>
+}
+
+impl<'a> Simulate<Execute<StrSequence<'a>>> for Recorder {
+    fn simulate(&mut self, simulatable: Execute<StrSequence<'a>>) {
+        self.timeline.push(simulatable)
+    }
+}
+
+impl Simulate<Sleep> for Recorder {
+    fn simulate(&mut self, simulatable: Sleep) {
+        self.timeline.wait(simulatable.0)
+    }
+}
+
+#[cfg(feature = "spin_sleep")]
+impl Simulate<SpinSleep> for Recorder {
+    fn simulate(&mut self, simulatable: SpinSleep) {
+        self.timeline.wait(simulatable.0)
+    }
+}
@@ -0,0 +1,217 @@
+//! Simplify a [`Timeline`] without changing what it does.
+//!
+//! Sleeps are already merged into the offset of events by a timeline,
+//! so zero and adjacent sleeps are gone as soon as a simulatable is compiled.
+//! [`Optimize`] goes further:
+//! - mouse moves that happen at the same time are merged into one,
+//! - scrolls and moves that do nothing are dropped.
+//!
+//! These are lossless and enabled by default.
+//! Lossy passes have to be enabled:
+//! - [`Optimize::downsample`] merges dense mouse moves,
+//!   the cursor skips intermediate positions but still ends up at the same place,
+//! - [`Optimize::drop_instant_taps`] drops a press directly followed by its release
+//!   at the same time. This changes what the timeline does:
+//!   a click or key press with no time between its press and release is gone.
+//!
+//! ```
+//! use kemuler::prelude::*;
+//! use kemuler::timeline::{Event, Timeline};
+//!
+//! let timeline = Timeline::compile(
+//!     (
+//!         MousePosition.move_by(1, 0),
+//!         MousePosition.move_by(2, 3),
+//!         MouseScroll.scroll_by(0, 0),
+//!         MousePosition.move_by(0, 1),
+//!     )
+//!         .seq(),
+//! );
+//! let timeline = timeline.optimize();
+//!
+//! assert_eq!(timeline.len(), 1);
+//! assert_eq!(timeline.events[0].event, Event::from(MousePosition.move_by(3, 4)));
+//! ```
+
+use std::time::Duration;
+
+use super::{Event, Timed, Timeline};
+use crate::input_event::*;
+
+/// Options of an optimization pass over a [`Timeline`].
+///
+/// See the [module documentation](self) for what each pass does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Optimize {
+    /// Merge mouse moves that happen at the same time.
+    pub coalesce_moves: bool,
+    /// Drop zero scrolls, zero relative moves, empty typing
+    /// and absolute moves to the previous absolute position with no relative move between.
+    pub drop_no_ops: bool,
+    /// Merge consecutive mouse moves that happen within this duration
+    /// from the first one, lossy.
+    pub downsample: Option<Duration>,
+    /// Drop a press directly followed by its release at the same time, lossy.
+    /// Off by default since those clicks and key presses are not simulated at all.
+    pub drop_instant_taps: bool,
+}
+
+impl Default for Optimize {
+    fn default() -> Self {
+        Optimize {
+            coalesce_moves: true,
+            drop_no_ops: true,
+            downsample: None,
+            drop_instant_taps: false,
+        }
+    }
+}
+
+impl Optimize {
+    /// Only lossless passes.
+    pub fn new() -> Optimize {
+        Optimize::default()
+    }
+
+    /// Merge mouse moves within `interval` of each other, see [`Optimize::downsample`].
+    pub fn downsample(mut self, interval: Duration) -> Optimize {
+        self.downsample = Some(interval);
+        self
+    }
+
+    /// Merge mouse moves so there are at most `rate` moves per second.
+    pub fn downsample_rate(self, rate: u32) -> Optimize {
+        self.downsample(Duration::from_secs(1) / rate.max(1))
+    }
+
+    /// Drop clicks and key presses that take no time, see [`Optimize::drop_instant_taps`].
+    ///
+    /// They are removed from the timeline, not shortened,
+    /// so only enable this if those inputs are not wanted.
+    pub fn drop_instant_taps(mut self) -> Optimize {
+        self.drop_instant_taps = true;
+        self
+    }
+
+    /// Optimize a timeline, its duration is never changed.
+    pub fn apply(&self, timeline: Timeline) -> Timeline {
+        let Timeline {
+            mut events,
+            duration,
+        } = timeline;
+        if self.drop_no_ops {
+            events = drop_no_ops(events);
+        }
+        if self.coalesce_moves || self.downsample.is_some() {
+            events = coalesce_moves(events, self.downsample.unwrap_or(Duration::ZERO));
+            if self.drop_no_ops {
+                // merged moves may cancel each other
+                events = drop_no_ops(events);
+            }
+        }
+        if self.drop_instant_taps {
+            events = drop_instant_taps(events);
+        }
+        Timeline { events, duration }
+    }
+}
+
+impl Timeline {
+    /// Optimize with only lossless passes, see [`Optimize`].
+    pub fn optimize(self) -> Timeline {
+        Optimize::new().apply(self)
+    }
+}
+
+fn drop_no_ops(events: Vec<Timed>) -> Vec<Timed> {
+    // last absolute position, forgotten after a relative move
+    // since the system may clamp the cursor
+    let mut position = None;
+    events
+        .into_iter()
+        .filter(|Timed { event, .. }| match event {
+            Event::Scroll(ChangeBy { by, .. }) => *by != (0, 0),
+            Event::Type(s) => !s.is_empty(),
+            Event::MoveBy(ChangeBy { by, .. }) => {
+                position = None;
+                *by != (0, 0)
+            }
+            Event::MoveTo(SetTo { to, .. }) => position.replace(*to) != Some(*to),
+            _ => true,
+        })
+        .collect()
+}
+
+/// Merge runs of mouse moves within `window` from the first move of the run.
+/// A merged move happens at the time of the last move merged into it.
+///
+/// `run_start` is only set while the last event of the result is a move.
+fn coalesce_moves(events: Vec<Timed>, window: Duration) -> Vec<Timed> {
+    let mut result: Vec<Timed> = Vec::with_capacity(events.len());
+    // offset of the first move merged into the last event of the result
+    let mut run_start = None;
+    for timed in events {
+        let in_run = run_start.is_some_and(|start| timed.offset <= start + window);
+        if let (true, Some(previous)) = (in_run, result.last_mut()) {
+            let merged = match &timed.event {
+                Event::MoveBy(ChangeBy { by, .. }) => merge_move_by(&mut previous.event, *by),
+                Event::MoveTo(_) => {
+                    previous.event = timed.event.clone();
+                    true
+                }
+                _ => false,
+            };
+            if merged {
+                previous.offset = timed.offset;
+                continue;
+            }
+        }
+        run_start = match timed.event {
+            Event::MoveBy(_) | Event::MoveTo(_) => Some(timed.offset),
+            _ => None,
+        };
+        result.push(timed);
+    }
+    result
+}
+
+/// Add a relative move to a move,
+/// returns `false` if it's not a move or the sum overflows.
+fn merge_move_by(event: &mut Event, (x, y): (i32, i32)) -> bool {
+    let (Event::MoveBy(ChangeBy { by: point, .. }) | Event::MoveTo(SetTo { to: point, .. })) =
+        event
+    else {
+        return false;
+    };
+    match (point.0.checked_add(x), point.1.checked_add(y)) {
+        (Some(x), Some(y)) => {
+            *point = (x, y);
+            true
+        }
+        _ => false,
+    }
+}
+
+fn drop_instant_taps(events: Vec<Timed>) -> Vec<Timed> {
+    let mut result: Vec<Timed> = Vec::with_capacity(events.len());
+    for timed in events {
+        if let Some(previous) = result.last() {
+            if previous.offset == timed.offset && is_tap(&previous.event, &timed.event) {
+                result.pop();
+                continue;
+            }
+        }
+        result.push(timed);
+    }
+    result
+}
+
+/// `press` is a press and `release` is its release.
+fn is_tap(press: &Event, release: &Event) -> bool {
+    match (press, release) {
+        (Event::Key(a), Event::Key(b)) => a.to && *b == a.invert(),
+        (Event::MouseButton(a), Event::MouseButton(b)) => a.to && *b == a.invert(),
+        (Event::Char(a), Event::Char(b)) => a.to && *b == a.invert(),
+        _ => false,
+    }
+}
@@ -0,0 +1,218 @@
+use std::time::Duration;
+
+use crate::combinator::Sleep;
+use crate::common_inputs::mouse_path::Path;
+use crate::layers::TimeScale;
+use crate::prelude::*;
+use crate::simulators::string_event_logger::StringEventLogger as S;
+
+use super::{optimize::Optimize, Event, Timed, Timeline};
+
+fn at(ms: u64, event: impl Into<Event>) -> Timed {
+    Timed {
+        offset: Duration::from_millis(ms),
+        event: event.into(),
+    }
+}
+
+#[test]
+fn timeline_compile() {
+    let x = (
+        Sleep::from_ms(5),
+        Char('a').click().during(Key::Shift.down()),
+        Sleep::from_ms(10),
+        MousePosition.move_to(1, 2),
+        StrSequence("hi").execute(),
+        Sleep::from_ms(3),
+    )
+        .seq();
+    let timeline = Timeline::compile(x);
+    assert_eq!(
+        timeline.events,
+        vec![
+            at(5, Key::Shift.down()),
+            at(5, Char('a').down()),
+            at(5, Char('a').up()),
+            at(5, Key::Shift.up()),
+            at(15, MousePosition.move_to(1, 2)),
+            at(15, Event::Type("hi".to_owned())),
+        ]
+    );
+    assert_eq!(timeline.duration, Duration::from_millis(18));
+}
+
+#[test]
+fn timeline_run_same_as_original() {
+    let x = (
+        MouseButton::Left.click(),
+        Key::Space.click().sleep_ms(1),
+        MouseScroll.scroll_by(0, 2),
+    )
+        .seq()
+        .repeat(3);
+
+    let mut original = S::new();
+    x.run_with(&mut original);
+
+    let mut flattened = S::new();
+    Timeline::compile(x).run_with(&mut flattened);
+
+    assert_eq!(original, flattened);
+}
+
+#[test]
+fn timeline_display() {
+    let timeline = Timeline::compile(
+        (
+            Key::Alt.down(),
+            Sleep::from_ms(100),
+            MousePosition.move_by(-1, 0),
+        )
+            .seq(),
+    );
+    assert_eq!(
+        timeline.to_string(),
+        "     0.000 ms  [set Alt to true]\n   \
+           100.000 ms  [change MousePosition by (-1, 0)]\n   \
+           100.000 ms  end\n"
+    );
+}
+
+#[test]
+fn timeline_optimize_lossless() {
+    let timeline = Timeline::compile(
+        (
+            MousePosition.move_to(10, 10),
+            MousePosition.move_by(5, 0),
+            Key::Tab.click(),
+            MousePosition.move_by(1, 1),
+            MousePosition.move_by(-1, -1),
+            MouseScroll.scroll_by(0, 0),
+            Sleep::from_ms(10),
+            MousePosition.move_to(15, 10),
+            MousePosition.move_by(0, 2),
+            Sleep::from_ms(10),
+            MousePosition.move_by(0, 2),
+            StrSequence("").execute(),
+        )
+            .seq(),
+    );
+    let optimized = timeline.clone().optimize();
+    assert_eq!(
+        optimized.events,
+        vec![
+            at(0, MousePosition.move_to(15, 10)),
+            // instant taps are only dropped when asked to
+            at(0, Key::Tab.down()),
+            at(0, Key::Tab.up()),
+            // the position is unknown after a relative move, the move to is kept
+            at(10, MousePosition.move_to(15, 12)),
+            at(20, MousePosition.move_by(0, 2)),
+        ]
+    );
+    assert_eq!(optimized.duration, timeline.duration);
+}
+
+#[test]
+fn timeline_optimize_move_to_after_move_by() {
+    // the system clamps the cursor at (0, 0), it ends up at (10, 0)
+    let timeline = Timeline::compile(
+        (
+            MousePosition.move_to(0, 0),
+            Sleep::from_ms(1),
+            MousePosition.move_by(-10, 0),
+            Sleep::from_ms(1),
+            MousePosition.move_by(10, 0),
+            Sleep::from_ms(1),
+            MousePosition.move_to(0, 0),
+            Sleep::from_ms(1),
+            MousePosition.move_to(0, 0),
+        )
+            .seq(),
+    );
+    let optimized = timeline.optimize();
+    assert_eq!(
+        optimized.events,
+        vec![
+            at(0, MousePosition.move_to(0, 0)),
+            at(1, MousePosition.move_by(-10, 0)),
+            at(2, MousePosition.move_by(10, 0)),
+            at(3, MousePosition.move_to(0, 0)),
+        ]
+    );
+}
+
+#[test]
+fn timeline_optimize_extreme_moves() {
+    let timeline = Timeline::compile(
+        (
+            MousePosition.move_to(i32::MAX, 0),
+            MousePosition.move_by(1, 0),
+            MousePosition.move_by(i32::MAX, 0),
+        )
+            .seq(),
+    );
+    let optimized = timeline.optimize();
+    // moves that would overflow are not merged
+    assert_eq!(
+        optimized.events,
+        vec![
+            at(0, MousePosition.move_to(i32::MAX, 0)),
+            at(0, MousePosition.move_by(1, 0)),
+            at(0, MousePosition.move_by(i32::MAX, 0)),
+        ]
+    );
+}
+
+#[test]
+fn timeline_optimize_lossy() {
+    let timeline = Timeline::compile(
+        (
+            MousePosition
+                .move_along(Path::linear((0, 0), (100, 0)))
+                .duration_ms(100)
+                .rate(100)
+                .relative(),
+            Char('a').click(),
+            Sleep::from_ms(1),
+            Char('b').click(),
+        )
+            .seq(),
+    );
+    let optimized = Optimize::new()
+        .downsample(Duration::from_millis(30))
+        .drop_instant_taps()
+        .apply(timeline);
+    let offsets: Vec<_> = optimized.iter().map(|timed| timed.offset).collect();
+    assert_eq!(offsets, [40, 80, 100].map(Duration::from_millis));
+    let total: i32 = optimized
+        .iter()
+        .map(|timed| match timed.event {
+            Event::MoveBy(e) => e.by.0,
+            _ => unreachable!("taps are dropped"),
+        })
+        .sum();
+    assert_eq!(total, 100);
+}
+
+#[test]
+fn timeline_scaled() {
+    let timeline = Timeline::compile(
+        (
+            Key::Alt.click(),
+            Sleep::from_ms(10),
+            Key::Tab.click(),
+            Sleep::from_ms(1000),
+            Key::Alt.click(),
+            Sleep::from_ms(2),
+        )
+            .seq(),
+    );
+    let scale = TimeScale::new(2.0)
+        .min(Duration::from_millis(5))
+        .max(Duration::from_millis(500));
+    let scaled = timeline.scaled(&scale);
+    let offsets: Vec<_> = scaled.iter().map(|timed| timed.offset).collect();
+    assert_eq!(offsets, [0, 5, 25, 30, 530, 535].map(Duration::from_millis));
+    assert_eq!(scaled.duration, Duration::from_millis(539));
+}